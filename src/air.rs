@@ -0,0 +1,187 @@
+use crate::color;
+use crate::display::{RenderOptions, label_for};
+use crate::models::{AirQualityResponse, ForecastResponse};
+use crate::table::print_rows;
+use std::fmt;
+
+// Air quality and pollen, from the open-meteo air quality api (CAMS models).
+// It only gives hourly values: the daily figures are the worst hour of the day.
+
+/// Levels of the European Air Quality Index (0 to 100+).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AqiLevel {
+    Good,
+    Fair,
+    Moderate,
+    Poor,
+    VeryPoor,
+    ExtremelyPoor,
+}
+
+impl AqiLevel {
+    pub fn from_index(aqi: f64) -> Self {
+        match aqi {
+            i if i < 20.0 => AqiLevel::Good,
+            i if i < 40.0 => AqiLevel::Fair,
+            i if i < 60.0 => AqiLevel::Moderate,
+            i if i < 80.0 => AqiLevel::Poor,
+            i if i < 100.0 => AqiLevel::VeryPoor,
+            _ => AqiLevel::ExtremelyPoor,
+        }
+    }
+}
+
+impl fmt::Display for AqiLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AqiLevel::Good => "good",
+            AqiLevel::Fair => "fair",
+            AqiLevel::Moderate => "moderate",
+            AqiLevel::Poor => "poor",
+            AqiLevel::VeryPoor => "very poor",
+            AqiLevel::ExtremelyPoor => "extremely poor",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Worst values of one day, `None` when the models have nothing for it.
+#[derive(Debug)]
+pub struct AirDay {
+    pub date: String,
+    pub european_aqi: Option<f64>,
+    pub pm2_5: Option<f64>,
+    pub pm10: Option<f64>,
+    pub ozone: Option<f64>,
+    pub nitrogen_dioxide: Option<f64>,
+    /// Highest concentration of each pollen type, the worst first, zeros left out
+    pub pollen: Vec<(&'static str, f64)>,
+}
+
+/// Highest value of each day, in the order of the days.
+fn daily_max(times: &[String], values: &[Option<f64>]) -> Vec<(String, Option<f64>)> {
+    let mut days: Vec<(String, Option<f64>)> = vec![];
+    for (time, value) in times.iter().zip(values) {
+        // "2026-10-18T14:00"
+        let date = time.get(..10).unwrap_or(time);
+        if days.last().is_none_or(|(last, _)| last != date) {
+            days.push((date.to_string(), None));
+        }
+        if let (Some((_, max)), Some(value)) = (days.last_mut(), value) {
+            *max = Some(max.map_or(*value, |m: f64| m.max(*value)));
+        }
+    }
+    days
+}
+
+/// The hourly forecast summed up per day.
+pub fn daily(air: &AirQualityResponse) -> Vec<AirDay> {
+    let h = &air.hourly;
+    let max = |values: &[Option<f64>]| -> Vec<Option<f64>> {
+        daily_max(&h.time, values)
+            .into_iter()
+            .map(|(_, max)| max)
+            .collect()
+    };
+    let pollen_types = [
+        ("alder", max(&h.alder_pollen)),
+        ("birch", max(&h.birch_pollen)),
+        ("grass", max(&h.grass_pollen)),
+        ("mugwort", max(&h.mugwort_pollen)),
+        ("olive", max(&h.olive_pollen)),
+        ("ragweed", max(&h.ragweed_pollen)),
+    ];
+    let (aqi, pm2_5, pm10) = (max(&h.european_aqi), max(&h.pm2_5), max(&h.pm10));
+    let (ozone, nitrogen_dioxide) = (max(&h.ozone), max(&h.nitrogen_dioxide));
+
+    daily_max(&h.time, &h.european_aqi)
+        .into_iter()
+        .enumerate()
+        .map(|(id, (date, _))| {
+            let mut pollen: Vec<(&'static str, f64)> = pollen_types
+                .iter()
+                .filter_map(|(name, values)| Some((*name, values[id]?)))
+                .filter(|&(_, value)| value > 0.0)
+                .collect();
+            pollen.sort_by(|a, b| b.1.total_cmp(&a.1));
+            AirDay {
+                date,
+                european_aqi: aqi[id],
+                pm2_5: pm2_5[id],
+                pm10: pm10[id],
+                ozone: ozone[id],
+                nitrogen_dioxide: nitrogen_dioxide[id],
+                pollen,
+            }
+        })
+        .collect()
+}
+
+/// Set the daily European AQI of each forecast day (`None` past the air quality forecast).
+pub fn annotate(forecast: &mut ForecastResponse, air: &AirQualityResponse) {
+    let days = daily(air);
+    let aqi = forecast
+        .daily
+        .time
+        .iter()
+        .map(|date| {
+            days.iter()
+                .find(|day| &day.date == date)
+                .and_then(|day| day.european_aqi)
+        })
+        .collect();
+    forecast.european_aqi = Some(aqi);
+}
+
+/// One row per day: AQI, pollutants and the main pollen.
+pub fn print_air(air: &AirQualityResponse, opts: &RenderOptions) {
+    let days = daily(air);
+    if days.is_empty() {
+        println!("No air quality data available");
+        return;
+    }
+    let units = &air.hourly_units;
+    println!(
+        "   Pollutants in {}, pollen in {} (highest hour of the day)\n",
+        units.pm2_5, units.birch_pollen
+    );
+
+    let missing = || "-".to_string();
+    let value = |v: Option<f64>| v.map_or_else(missing, |v| format!("{v:.0}"));
+    let rows: Vec<Vec<String>> = days
+        .iter()
+        .enumerate()
+        .map(|(id, day)| {
+            let aqi = day.european_aqi.map_or_else(missing, |aqi| {
+                let level = AqiLevel::from_index(aqi);
+                let text = format!("{aqi:.0} {level}");
+                if level >= AqiLevel::Poor {
+                    color::severe(&text, opts.color)
+                } else {
+                    text
+                }
+            });
+            let pollen = match &day.pollen[..] {
+                [] => missing(),
+                pollen => pollen
+                    .iter()
+                    .take(2)
+                    .map(|(name, value)| format!("{name} {value:.0}"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            };
+            vec![
+                format!("{} {}", label_for(id, &day.date, opts), day.date),
+                aqi,
+                value(day.pm2_5),
+                value(day.pm10),
+                value(day.ozone),
+                value(day.nitrogen_dioxide),
+                pollen,
+            ]
+        })
+        .collect();
+
+    let headers = ["Day", "EAQI", "PM2.5", "PM10", "O3", "NO2", "Pollen"];
+    print_rows(&headers, &rows, &[0, 1, 6], opts);
+}
@@ -0,0 +1,481 @@
+use crate::db::Store;
+use crate::models::{City, ForecastResponse};
+use crate::notify::{Sink, check_sinks};
+use crate::open_meteo::{
+    DEFAULT_FORECAST_DAYS, MAX_FORECAST_DAYS, get_city_forecast_days, get_geocode,
+};
+use crate::wmo::{Category, category, is_severe};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+// Alert rules, read from a TOML file and checked against the daily forecasts:
+//
+//   [[rule]]
+//   name = "Frost in Brussels"
+//   city = "Brussels"          # leave it out for every stored city
+//   when = "min < 0"           # min/max/mean (°C) or rain (mm), compared with < <= > >= =
+//   within_days = 2            # today and the next 2 days, or `day = 1` for tomorrow only
+//
+//   [[rule]]
+//   name = "Thunderstorm tomorrow"
+//   when = "thunderstorm"      # a weather category (see wmo::Category) or "severe"
+//   day = 1
+//
+// Without `day` or `within_days` a rule only looks at today.
+// The same file lists the `[[sink]]`s to notify, see the notify module.
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<Rule>,
+    #[serde(default, rename = "sink")]
+    pub sinks: Vec<Sink>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    /// Only this city (stored or geocoded), every stored city if `None`.
+    pub city: Option<String>,
+    pub when: Condition,
+    pub day: Option<usize>,
+    pub within_days: Option<usize>,
+}
+
+impl Rule {
+    /// Day indices the rule looks at (0 is today).
+    pub fn days(&self) -> RangeInclusive<usize> {
+        match (self.day, self.within_days) {
+            (Some(day), _) => day..=day,
+            (None, Some(within)) => 0..=within,
+            (None, None) => 0..=0,
+        }
+    }
+
+    fn applies_to(&self, city: &City) -> bool {
+        self.city
+            .as_ref()
+            .is_none_or(|name| name.trim().eq_ignore_ascii_case(&city.name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Min,
+    Max,
+    Mean,
+    Rain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+/// What makes a rule fire, parsed from its `when` string.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Condition {
+    Compare { field: Field, op: Op, value: f64 },
+    Weather(Category),
+    Severe,
+}
+
+impl TryFrom<String> for Condition {
+    type Error = String;
+
+    fn try_from(when: String) -> Result<Self, Self::Error> {
+        let when = when.trim().to_lowercase();
+        if when == "severe" {
+            return Ok(Condition::Severe);
+        }
+        if let Ok(category) = when.parse() {
+            return Ok(Condition::Weather(category));
+        }
+
+        let [field, op, value] = when.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(format!(
+                "'{when}' is not a weather category nor a comparison like 'min < 0'"
+            ));
+        };
+        let field = match field {
+            "min" => Field::Min,
+            "max" => Field::Max,
+            "mean" => Field::Mean,
+            "rain" => Field::Rain,
+            other => return Err(format!("Unknown field '{other}' (min, max, mean or rain)")),
+        };
+        let op = match op {
+            "<" => Op::Less,
+            "<=" => Op::LessOrEqual,
+            ">" => Op::Greater,
+            ">=" => Op::GreaterOrEqual,
+            "=" | "==" => Op::Equal,
+            other => return Err(format!("Unknown comparison '{other}' (< <= > >= =)")),
+        };
+        let value = value
+            .parse()
+            .map_err(|_| format!("'{value}' is not a number"))?;
+        Ok(Condition::Compare { field, op, value })
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Field::Min => "min",
+            Field::Max => "max",
+            Field::Mean => "mean",
+            Field::Rain => "rain",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            Op::Less => "<",
+            Op::LessOrEqual => "<=",
+            Op::Greater => ">",
+            Op::GreaterOrEqual => ">=",
+            Op::Equal => "=",
+        };
+        f.write_str(op)
+    }
+}
+
+impl Op {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Op::Less => left < right,
+            Op::LessOrEqual => left <= right,
+            Op::Greater => left > right,
+            Op::GreaterOrEqual => left >= right,
+            Op::Equal => left == right,
+        }
+    }
+}
+
+impl Condition {
+    /// Description of what matched on `day`, `None` if the condition doesn't hold.
+    fn check(&self, forecast: &ForecastResponse, day: usize) -> Option<String> {
+        let daily = &forecast.daily;
+        let units = &forecast.daily_units;
+        match *self {
+            Condition::Compare { field, op, value } => {
+                let (values, unit) = match field {
+                    Field::Min => (&daily.temperature_2m_min, &units.temperature_2m_min),
+                    Field::Max => (&daily.temperature_2m_max, &units.temperature_2m_max),
+                    Field::Mean => (&daily.temperature_2m_mean, &units.temperature_2m_mean),
+                    Field::Rain => (&daily.precipitation_sum, &units.precipitation_sum),
+                };
+                let actual = *values.get(day)?;
+                op.holds(actual, value)
+                    .then(|| format!("{field} {actual:.1} {unit} {op} {value}"))
+            }
+            Condition::Weather(wanted) => {
+                let code = *daily.weather_code.get(day)?;
+                (category(code) == wanted).then(|| format!("{wanted} (code {code})"))
+            }
+            Condition::Severe => {
+                let code = *daily.weather_code.get(day)?;
+                is_severe(code).then(|| format!("severe weather (code {code})"))
+            }
+        }
+    }
+}
+
+/// A rule that fired for a city on a given day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub rule: String,
+    pub city: String,
+    /// Day index, 0 is today
+    pub day: usize,
+    pub date: String,
+    pub detail: String,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} on {}: {}",
+            self.rule, self.city, self.date, self.detail
+        )
+    }
+}
+
+/// Read and check the rules file.
+pub fn load(path: &Path) -> Result<AlertConfig> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Couldn't read rules file {}", path.display()))?;
+    parse(&text).with_context(|| format!("Invalid rules file {}", path.display()))
+}
+
+/// Parse and check the rules.
+fn parse(text: &str) -> Result<AlertConfig> {
+    let file: AlertConfig = toml::from_str(text)?;
+
+    let last_day = usize::from(MAX_FORECAST_DAYS) - 1;
+    for rule in &file.rules {
+        if rule.name.trim().is_empty() {
+            bail!("Every rule needs a name");
+        }
+        if rule.day.is_some() && rule.within_days.is_some() {
+            bail!("Rule '{}': give day or within_days, not both", rule.name);
+        }
+        if *rule.days().end() > last_day {
+            bail!(
+                "Rule '{}': the forecast only goes {last_day} days ahead",
+                rule.name
+            );
+        }
+    }
+    check_sinks(&file.sinks)?;
+    Ok(file)
+}
+
+/// Forecasts of the cities that could be fetched, and why the others couldn't.
+#[derive(Debug, Default)]
+pub struct Forecasts {
+    pub fetched: Vec<(City, ForecastResponse)>,
+    pub failed: Vec<anyhow::Error>,
+}
+
+/// Forecasts of every city the rules look at (and of every stored city with `all_stored`),
+/// long enough for the furthest rule. A city that can't be found or fetched doesn't stop the
+/// others, only a database failure does.
+pub async fn fetch_forecasts(store: &Store, rules: &[Rule], all_stored: bool) -> Result<Forecasts> {
+    let furthest = rules.iter().map(|rule| *rule.days().end()).max();
+    let days = furthest.map_or(DEFAULT_FORECAST_DAYS, |day| {
+        DEFAULT_FORECAST_DAYS.max(day as u8 + 1)
+    });
+
+    let mut forecasts = Forecasts::default();
+    let mut cities: Vec<City> = vec![];
+    if all_stored || rules.iter().any(|rule| rule.city.is_none()) {
+        cities.extend(store.cities()?.into_iter().map(|stored| stored.city));
+    }
+    for name in rules.iter().filter_map(|rule| rule.city.as_deref()) {
+        if cities
+            .iter()
+            .any(|city| city.name.eq_ignore_ascii_case(name.trim()))
+        {
+            continue;
+        }
+        let city = match store.find_city(name)? {
+            Some(stored) => stored.city,
+            None => match geocode(name).await {
+                Ok(city) => city,
+                Err(e) => {
+                    forecasts.failed.push(e);
+                    continue;
+                }
+            },
+        };
+        cities.push(city);
+    }
+
+    for city in cities {
+        match get_city_forecast_days(&city, days).await {
+            Ok(forecast) => forecasts.fetched.push((city, forecast)),
+            Err(e) => forecasts.failed.push(e),
+        }
+    }
+    Ok(forecasts)
+}
+
+/// City of a rule which isn't stored, under the name of the rule so that it still matches.
+async fn geocode(name: &str) -> Result<City> {
+    let found = get_geocode(name)
+        .await
+        .with_context(|| format!("Couldn't look up '{name}'"))?;
+    Ok(City {
+        name: name.trim().to_string(),
+        ..City::try_from(found).with_context(|| format!("No city found for '{name}'"))?
+    })
+}
+
+/// Every (rule, city, day) for which a rule fires.
+pub fn evaluate(rules: &[Rule], forecasts: &[(City, ForecastResponse)]) -> Vec<Alert> {
+    let mut alerts = vec![];
+    for rule in rules {
+        for (city, forecast) in forecasts.iter().filter(|(city, _)| rule.applies_to(city)) {
+            for day in rule.days() {
+                let Some(detail) = rule.when.check(forecast, day) else {
+                    continue;
+                };
+                alerts.push(Alert {
+                    rule: rule.name.clone(),
+                    city: city.name.clone(),
+                    day,
+                    date: forecast.daily.time.get(day).cloned().unwrap_or_default(),
+                    detail,
+                });
+            }
+        }
+    }
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ForecastBuilder;
+
+    fn when(text: &str) -> Result<Condition, String> {
+        Condition::try_from(text.to_string())
+    }
+
+    fn rule(name: &str, when: &str, day: Option<usize>, within_days: Option<usize>) -> Rule {
+        Rule {
+            name: name.to_string(),
+            city: None,
+            when: Condition::try_from(when.to_string()).unwrap(),
+            day,
+            within_days,
+        }
+    }
+
+    fn city(name: &str) -> City {
+        City {
+            name: name.to_string(),
+            lat: 50.8505,
+            long: 4.3488,
+            coastal: false,
+        }
+    }
+
+    /// Three days: frost and rain today, a thunderstorm tomorrow, mild after.
+    fn forecast() -> ForecastResponse {
+        ForecastBuilder::default()
+            .day("2026-10-18", 61, -2.5, 4.0)
+            .rain(6.2)
+            .day("2026-10-19", 95, 3.0, 12.0)
+            .rain(12.0)
+            .day("2026-10-20", 2, 8.0, 16.0)
+            .build()
+    }
+
+    #[test]
+    fn comparisons_are_parsed() {
+        assert_eq!(
+            when("min < 0"),
+            Ok(Condition::Compare {
+                field: Field::Min,
+                op: Op::Less,
+                value: 0.0
+            })
+        );
+        assert_eq!(
+            when("  RAIN >= 10.5 "),
+            Ok(Condition::Compare {
+                field: Field::Rain,
+                op: Op::GreaterOrEqual,
+                value: 10.5
+            })
+        );
+        assert_eq!(
+            when("max == -3"),
+            Ok(Condition::Compare {
+                field: Field::Max,
+                op: Op::Equal,
+                value: -3.0
+            })
+        );
+    }
+
+    #[test]
+    fn categories_and_severe_are_parsed() {
+        assert_eq!(
+            when("thunderstorm"),
+            Ok(Condition::Weather(Category::Thunderstorm))
+        );
+        // "rain" alone is the category, with a comparison the amount
+        assert_eq!(when("Rain"), Ok(Condition::Weather(Category::Rain)));
+        assert_eq!(when("severe"), Ok(Condition::Severe));
+    }
+
+    #[test]
+    fn malformed_conditions_are_rejected() {
+        for text in [
+            "",
+            "hail",
+            "min<0",
+            "min < zero",
+            "wind > 50",
+            "min => 0",
+            "min < 0 c",
+        ] {
+            assert!(when(text).is_err(), "'{text}' was accepted");
+        }
+    }
+
+    #[test]
+    fn malformed_rules_are_rejected() {
+        let invalid = [
+            // no name
+            "[[rule]]\nname = \" \"\nwhen = \"severe\"",
+            // both day and within_days
+            "[[rule]]\nname = \"a\"\nwhen = \"severe\"\nday = 1\nwithin_days = 2",
+            // past the forecast
+            "[[rule]]\nname = \"a\"\nwhen = \"severe\"\nday = 16",
+            // unknown key
+            "[[rule]]\nname = \"a\"\nwhen = \"severe\"\ndays = 2",
+            // bad condition
+            "[[rule]]\nname = \"a\"\nwhen = \"min < cold\"",
+        ];
+        for text in invalid {
+            assert!(parse(text).is_err(), "accepted:\n{text}");
+        }
+
+        let config = parse("[[rule]]\nname = \"a\"\nwhen = \"min < 0\"\nwithin_days = 15").unwrap();
+        assert_eq!(config.rules[0].days(), 0..=15);
+    }
+
+    #[test]
+    fn rules_fire_on_their_days() {
+        let forecasts = [(city("Brussels"), forecast())];
+        let rules = [
+            rule("Frost", "min < 0", None, None),
+            rule("Wet", "rain > 5", None, Some(2)),
+            rule("Storm", "thunderstorm", Some(1), None),
+            rule("Severe today", "severe", None, None),
+        ];
+        let alerts = evaluate(&rules, &forecasts);
+        let fired: Vec<(&str, usize)> = alerts.iter().map(|a| (a.rule.as_str(), a.day)).collect();
+        assert_eq!(fired, [("Frost", 0), ("Wet", 0), ("Wet", 1), ("Storm", 1)]);
+        assert_eq!(alerts[0].date, "2026-10-18");
+        assert_eq!(alerts[0].detail, "min -2.5 °C < 0");
+        assert_eq!(alerts[3].detail, "thunderstorm (code 95)");
+    }
+
+    #[test]
+    fn rules_only_look_at_their_city() {
+        let forecasts = [(city("Brussels"), forecast()), (city("Ghent"), forecast())];
+        let mut frost = rule("Frost", "min < 0", None, None);
+        frost.city = Some(" ghent".to_string());
+        let alerts = evaluate(&[frost], &forecasts);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].city, "Ghent");
+    }
+
+    #[test]
+    fn days_past_the_forecast_are_ignored() {
+        let forecasts = [(city("Brussels"), forecast())];
+        let alerts = evaluate(&[rule("Severe", "severe", None, Some(10))], &forecasts);
+        let days: Vec<usize> = alerts.iter().map(|a| a.day).collect();
+        assert_eq!(days, [1]);
+    }
+}
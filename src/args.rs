@@ -1,18 +1,176 @@
-use clap::{Args, Parser, Subcommand};
+use crate::open_meteo::{
+    DEFAULT_FORECAST_DAYS, MAX_AIR_QUALITY_DAYS, MAX_FORECAST_DAYS, MAX_MARINE_DAYS, MAX_RAIN_HOURS,
+};
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
 pub struct WeatherArgs {
     #[clap(subcommand)]
     pub command: Option<Commands>, // Option to make it work with None too.
+
+    /// Draw with plain ASCII instead of box drawing characters and emoji.
+    #[arg(long, global = true)]
+    pub no_unicode: bool,
+
+    /// SQLite database with the synchronized cities (created if missing), by default
+    /// deca-weather.db in $XDG_DATA_HOME/deca-weather or ~/.local/share/deca-weather.
+    #[arg(long, global = true, env = "DECA_WEATHER_DB")]
+    pub db: Option<PathBuf>,
+
+    /// When to colour the output (auto: only on a terminal, and if NO_COLOR isn't set).
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Show daily weather forcast for our list of favourite Belgian cities.
-    List,
+    List(ListArgs),
     /// Get weather of a registered favourite Belgian city.
     Get(GetArgs),
+    /// Show the synchronized cities stored in the database.
+    Cities,
+    /// Add a city to synchronize (coordinates from the geocoding api unless given).
+    Add(AddArgs),
+    /// Remove a city from the database.
+    Remove(RemoveArgs),
+    /// Chart temperature and precipitation trends of a city over the forecast period.
+    Chart(ChartArgs),
+    /// Full-screen dashboard of the favourite cities.
+    Tui(TuiArgs),
+    /// Start the HTTP API server.
+    Serve(ServeArgs),
+    /// Export the forecasts of the stored cities as Prometheus metrics.
+    Exporter(ExporterArgs),
+    /// Weather alerts from the rules file.
+    Alerts(AlertsArgs),
+    /// Keep running: refresh the cities on a schedule and check the alerts, until stopped.
+    Watch(WatchArgs),
+    /// Show how the forecast of a stored city changed since the previous fetch.
+    Diff(DiffArgs),
+    /// Show the past weather of a city between two dates.
+    History(HistoryArgs),
+    /// Check the saved forecasts against the observed weather (errors per lead time).
+    Verify(VerifyArgs),
+    /// Air quality and pollen forecast of a city.
+    Air(AirArgs),
+    /// Waves, swell and sea temperature near a coastal city.
+    Marine(MarineArgs),
+    /// The forecasts of several weather models side by side.
+    CompareModels(CompareModelsArgs),
+    /// When it will rain in a city over the next hours.
+    Rain(RainArgs),
+    /// Sunrise, sunset and daylight of a city.
+    Sun(SunArgs),
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ListArgs {
+    /// Show a compact comparison table (one row per city) instead of cards.
+    #[arg(long)]
+    pub table: bool,
+
+    /// Sort the table rows, based on the first selected day.
+    #[arg(long, value_enum, requires = "table")]
+    pub sort: Option<SortBy>,
+
+    /// Include forcast for tomorrow.
+    #[arg(long)]
+    pub tomorrow: bool,
+
+    /// Include forcast for the day after tomorrow.
+    #[arg(long)]
+    pub day_after: bool,
+
+    /// Add the European air quality index to the cards.
+    #[arg(long, conflicts_with = "table")]
+    pub air: bool,
+
+    /// Add the range of the ensemble forecast (p10 to p90) and the rain chance to the cards.
+    #[arg(long, conflicts_with = "table")]
+    pub ensemble: bool,
+
+    /// Weather model of the forecast (snapshots are only saved for best_match).
+    #[arg(long, value_enum, default_value_t = WeatherModel::BestMatch)]
+    pub model: WeatherModel,
+}
+
+/// Sorting keys for the comparison table.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SortBy {
+    /// Warmest maximum temperature first.
+    Max,
+    /// Coldest minimum temperature first.
+    Min,
+    /// City name, alphabetically.
+    Name,
+    /// Wettest (highest precipitation sum) first.
+    Rain,
+}
+
+/// Colour modes for --color.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Weather models for --model, named like the `models` parameter of open-meteo.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum WeatherModel {
+    /// Open-meteo picks the best models for the place.
+    #[default]
+    BestMatch,
+    /// DWD ICON, the global and European runs combined.
+    IconSeamless,
+    /// DWD ICON-D2, 2 km over Central Europe, 2 days.
+    IconD2,
+    /// DWD ICON-EU, 7 km over Europe, 5 days.
+    IconEu,
+    /// ECMWF IFS 0.25°, global, 15 days.
+    EcmwfIfs025,
+    /// Météo-France ARPEGE and AROME combined.
+    MeteofranceSeamless,
+    /// Météo-France AROME, 1.3 km over France and Benelux, 2 days.
+    MeteofranceAromeFrance,
+    /// KNMI HARMONIE, 2 km over the Netherlands and Belgium, 2 days.
+    KnmiSeamless,
+    /// NOAA GFS, global, 16 days.
+    GfsSeamless,
+    /// UK Met Office, global and UK runs combined.
+    UkmoSeamless,
+}
+
+impl WeatherModel {
+    /// Name in the open-meteo api (and in the suffix of the fields of multi-model answers).
+    pub fn api_name(self) -> &'static str {
+        match self {
+            WeatherModel::BestMatch => "best_match",
+            WeatherModel::IconSeamless => "icon_seamless",
+            WeatherModel::IconD2 => "icon_d2",
+            WeatherModel::IconEu => "icon_eu",
+            WeatherModel::EcmwfIfs025 => "ecmwf_ifs025",
+            WeatherModel::MeteofranceSeamless => "meteofrance_seamless",
+            WeatherModel::MeteofranceAromeFrance => "meteofrance_arome_france",
+            WeatherModel::KnmiSeamless => "knmi_seamless",
+            WeatherModel::GfsSeamless => "gfs_seamless",
+            WeatherModel::UkmoSeamless => "ukmo_seamless",
+        }
+    }
+}
+
+impl fmt::Display for WeatherModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.api_name())
+    }
 }
 
 #[derive(Args, Debug)]
@@ -27,4 +185,255 @@ pub struct GetArgs {
     /// Include forcast for the day after tomorrow.
     #[arg(long)] // -- flag for day after tomorrow
     pub day_after: bool,
+
+    /// Add the European air quality index to the cards.
+    #[arg(long)]
+    pub air: bool,
+
+    /// Add the range of the ensemble forecast (p10 to p90) and the rain chance to the cards.
+    #[arg(long)]
+    pub ensemble: bool,
+
+    /// Weather model of the forecast (snapshots are only saved for best_match).
+    #[arg(long, value_enum, default_value_t = WeatherModel::BestMatch)]
+    pub model: WeatherModel,
+
+    /// Add the best hours to cycle to work and back, today and tomorrow.
+    #[arg(long)]
+    pub bike: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// Latitude, to skip the geocoding lookup.
+    #[arg(long, requires = "long", allow_negative_numbers = true)]
+    pub lat: Option<f64>,
+
+    /// Longitude, to skip the geocoding lookup.
+    #[arg(long, requires = "lat", allow_negative_numbers = true)]
+    pub long: Option<f64>,
+
+    /// The city is on the sea: show the marine forecast with the weather.
+    #[arg(long)]
+    pub coastal: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RemoveArgs {
+    /// Name of the city.
+    pub city: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ChartArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// Number of forecast days to chart.
+    #[arg(long, default_value_t = DEFAULT_FORECAST_DAYS, value_parser = clap::value_parser!(u8).range(1..=MAX_FORECAST_DAYS as i64))]
+    pub days: u8,
+
+    /// Weather model of the forecast.
+    #[arg(long, value_enum, default_value_t = WeatherModel::BestMatch)]
+    pub model: WeatherModel,
+}
+
+#[derive(Args, Debug)]
+pub struct TuiArgs {
+    /// Minutes between two background refreshes of every city.
+    #[arg(long, default_value_t = 15, value_parser = clap::value_parser!(u64).range(1..))]
+    pub refresh: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:3000")]
+    pub addr: SocketAddr,
+
+    /// Seconds an upstream forecast is cached and reused.
+    #[arg(long, default_value_t = 600)]
+    pub cache_ttl: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct ExporterArgs {
+    /// Address to serve /metrics on.
+    #[arg(long, default_value = "127.0.0.1:9184")]
+    pub addr: SocketAddr,
+
+    /// Minutes between two refreshes of every city.
+    #[arg(long, default_value_t = 15, value_parser = clap::value_parser!(u64).range(1..))]
+    pub refresh: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct AlertsArgs {
+    /// TOML file with the alert rules.
+    #[arg(
+        long,
+        global = true,
+        env = "DECA_WEATHER_RULES",
+        default_value = "alerts.toml"
+    )]
+    pub rules: PathBuf,
+
+    #[command(subcommand)]
+    pub command: AlertsCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AlertsCommand {
+    /// Check the rules against the current forecasts, exits with 2 if any alert fires (1 on errors).
+    Check(AlertsCheckArgs),
+    /// Send a sample alert to every sink of the rules file.
+    TestSinks,
+}
+
+#[derive(Args, Debug)]
+pub struct AlertsCheckArgs {
+    /// Send the new alerts to the sinks of the rules file.
+    #[arg(long)]
+    pub notify: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Minutes between two refreshes.
+    #[arg(long, default_value_t = 15, value_parser = clap::value_parser!(u64).range(1..))]
+    pub every: u64,
+
+    /// Print what changed on each refresh instead of redrawing the table (for logs and services).
+    #[arg(long)]
+    pub log: bool,
+
+    /// Alert rules to check on each refresh.
+    #[arg(long, env = "DECA_WEATHER_RULES")]
+    pub rules: Option<PathBuf>,
+
+    /// Send new alerts to the sinks of the rules file.
+    #[arg(long, requires = "rules")]
+    pub notify: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Name of a stored city.
+    pub city: String,
+
+    /// Compare the two last saved snapshots instead of fetching a new forecast.
+    #[arg(long)]
+    pub stored: bool,
+
+    /// Smallest min/max temperature change to show, in °C.
+    #[arg(long, default_value_t = 1.0, value_parser = threshold)]
+    pub temp_threshold: f64,
+
+    /// Smallest precipitation change to show, in mm.
+    #[arg(long, default_value_t = 1.0, value_parser = threshold)]
+    pub rain_threshold: f64,
+}
+
+/// A change threshold: 0 shows every change, below it would show every day.
+fn threshold(value: &str) -> Result<f64, String> {
+    let threshold: f64 = value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a number"))?;
+    if threshold >= 0.0 {
+        Ok(threshold)
+    } else {
+        Err(format!("{value} is not 0 or more"))
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// First day (YYYY-MM-DD).
+    #[arg(long)]
+    pub from: NaiveDate,
+
+    /// Last day (YYYY-MM-DD), the same as --from if not given.
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Name of a stored city, every stored city if not given.
+    pub city: Option<String>,
+
+    /// Furthest lead time to check, in days (0 is a forecast for the day it was fetched).
+    #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(i64).range(0..=MAX_FORECAST_DAYS as i64))]
+    pub max_lead: i64,
+}
+
+#[derive(Args, Debug)]
+pub struct AirArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// Number of days, pollen is only forecast for the first 4.
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=MAX_AIR_QUALITY_DAYS as i64))]
+    pub days: u8,
+}
+
+#[derive(Args, Debug)]
+pub struct MarineArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// Number of days.
+    #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u8).range(1..=MAX_MARINE_DAYS as i64))]
+    pub days: u8,
+}
+
+#[derive(Args, Debug)]
+pub struct CompareModelsArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// Models to compare, separated by commas.
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [
+        WeatherModel::BestMatch,
+        WeatherModel::IconD2,
+        WeatherModel::MeteofranceAromeFrance,
+        WeatherModel::KnmiSeamless,
+        WeatherModel::EcmwfIfs025,
+        WeatherModel::GfsSeamless,
+    ])]
+    pub models: Vec<WeatherModel>,
+
+    /// Number of days (short range models stop after 2 days).
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=MAX_FORECAST_DAYS as i64))]
+    pub days: u8,
+}
+
+#[derive(Args, Debug)]
+pub struct RainArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// Number of hours to look ahead.
+    #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(u16).range(1..=MAX_RAIN_HOURS as i64))]
+    pub hours: u16,
+}
+
+#[derive(Args, Debug)]
+pub struct SunArgs {
+    /// Name of the city.
+    pub city: String,
+
+    /// Number of days.
+    #[arg(long, default_value_t = DEFAULT_FORECAST_DAYS, value_parser = clap::value_parser!(u8).range(1..=MAX_FORECAST_DAYS as i64))]
+    pub days: u8,
+
+    /// Weather model of the forecast.
+    #[arg(long, value_enum, default_value_t = WeatherModel::BestMatch)]
+    pub model: WeatherModel,
 }
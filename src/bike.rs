@@ -0,0 +1,129 @@
+use crate::display::{RenderOptions, day_label};
+use crate::models::HourlyResponse;
+use crate::table::print_rows;
+use crate::wind;
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use std::ops::RangeInclusive;
+
+// Best hours to cycle to work and back: each hour of the usual commute times gets a penalty
+// for rain, wind and cold, the lowest one wins. Rain weighs the most, nobody likes arriving
+// soaked; a headwind only matters when it's strong.
+
+/// Hours of the hourly forecast the summary looks at (today and tomorrow).
+pub const BIKE_HOURS: u16 = 48;
+/// Departure hours to work and back home.
+const MORNING: RangeInclusive<u32> = 6..=9;
+const EVENING: RangeInclusive<u32> = 16..=19;
+/// Wind (km/h) and gusts from which cycling gets harder.
+const COMFORTABLE_WIND: f64 = 15.0;
+const COMFORTABLE_GUSTS: f64 = 40.0;
+/// Below this temperature (°C) gloves are needed.
+const COMFORTABLE_TEMPERATURE: f64 = 5.0;
+
+/// One hour of the forecast seen from a bike.
+#[derive(Debug)]
+struct BikeHour {
+    time: NaiveDateTime,
+    temperature: f64,
+    probability: Option<u8>,
+    precipitation: f64,
+    wind_speed: f64,
+    wind_direction: f64,
+    gusts: f64,
+}
+
+impl BikeHour {
+    /// 0 is perfect, each point is a bit worse
+    fn penalty(&self) -> f64 {
+        let rain = f64::from(self.probability.unwrap_or(0)) / 10.0 + self.precipitation * 10.0;
+        let wind = (self.wind_speed - COMFORTABLE_WIND).max(0.0) / 3.0
+            + (self.gusts - COMFORTABLE_GUSTS).max(0.0) / 5.0;
+        let cold = (COMFORTABLE_TEMPERATURE - self.temperature).max(0.0) / 2.0;
+        rain + wind + cold
+    }
+
+    /// "08:00  dry, ↙ NE 12 km/h, 9 °C"
+    fn describe(&self, wind_unit: &str, opts: &RenderOptions) -> String {
+        let rain = match self.probability {
+            Some(p) if p >= 20 || self.precipitation > 0.0 => format!("{p}% rain"),
+            None if self.precipitation > 0.0 => format!("{:.1} mm", self.precipitation),
+            _ => "dry".to_string(),
+        };
+        format!(
+            "{}  {rain}, {}, {:.0} °C",
+            self.time.format("%H:%M"),
+            wind::format_wind(
+                self.wind_speed,
+                self.wind_direction,
+                wind_unit,
+                opts.unicode
+            ),
+            self.temperature
+        )
+    }
+}
+
+fn hours(forecast: &HourlyResponse) -> Vec<BikeHour> {
+    let h = &forecast.hourly;
+    h.time
+        .iter()
+        .enumerate()
+        .filter_map(|(id, time)| {
+            Some(BikeHour {
+                time: NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").ok()?,
+                temperature: h.temperature_2m[id],
+                probability: h.precipitation_probability[id],
+                precipitation: h.precipitation[id],
+                wind_speed: h.wind_speed_10m[id],
+                wind_direction: h.wind_direction_10m[id],
+                gusts: h.wind_gusts_10m[id],
+            })
+        })
+        .collect()
+}
+
+/// Hour with the lowest penalty of a day within `window`, `None` once it's past.
+fn best<'a>(
+    hours: &'a [BikeHour],
+    date: NaiveDate,
+    window: &RangeInclusive<u32>,
+) -> Option<&'a BikeHour> {
+    hours
+        .iter()
+        .filter(|h| h.time.date() == date && window.contains(&h.time.hour()))
+        .min_by(|a, b| a.penalty().total_cmp(&b.penalty()))
+}
+
+/// One row per day: the best hour to leave in the morning and in the evening.
+pub fn print_bike(forecast: &HourlyResponse, opts: &RenderOptions) {
+    let hours = hours(forecast);
+    let Some(first) = hours.first().map(|h| h.time.date()) else {
+        println!("No hourly data available");
+        return;
+    };
+
+    let unit = &forecast.hourly_units.wind_speed_10m;
+    let missing = || "-".to_string();
+    let mut dates: Vec<NaiveDate> = hours.iter().map(|h| h.time.date()).collect();
+    dates.dedup();
+    let rows: Vec<Vec<String>> = dates
+        .iter()
+        .map(|&date| {
+            let id = (date - first).num_days() as usize;
+            let cell = |window| {
+                best(&hours, date, window).map_or_else(missing, |h| h.describe(unit, opts))
+            };
+            vec![day_label(id), cell(&MORNING), cell(&EVENING)]
+        })
+        .filter(|row| row[1..].iter().any(|cell| cell != "-"))
+        .collect();
+
+    println!(
+        "   Best hours to cycle, leaving between {}:00-{}:00 and {}:00-{}:00\n",
+        MORNING.start(),
+        MORNING.end(),
+        EVENING.start(),
+        EVENING.end()
+    );
+    print_rows(&["Day", "Morning", "Evening"], &rows, &[0, 1, 2], opts);
+}
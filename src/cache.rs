@@ -0,0 +1,233 @@
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+// In-memory TTL cache for upstream (open-meteo) responses.
+// Concurrent requests for the same key share a single upstream call: the first one
+// fetches, the others wait on the same `OnceCell`. A failure is shared with the requests
+// waiting for it, then the slot is dropped so that the next request tries again.
+
+/// Coordinates are rounded to 2 decimals (~1 km), close enough for a forecast.
+const COORDINATE_PRECISION: f64 = 100.0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    lat: i64,
+    long: i64,
+    /// Whatever else changes the answer (requested variables, days...).
+    variables: String,
+}
+
+impl CacheKey {
+    pub fn new(lat: f64, long: f64, variables: impl Into<String>) -> Self {
+        CacheKey {
+            lat: (lat * COORDINATE_PRECISION).round() as i64,
+            long: (long * COORDINATE_PRECISION).round() as i64,
+            variables: variables.into(),
+        }
+    }
+
+    /// Rounded coordinates, to fetch exactly what the key stands for.
+    pub fn coordinates(&self) -> (f64, f64) {
+        (
+            self.lat as f64 / COORDINATE_PRECISION,
+            self.long as f64 / COORDINATE_PRECISION,
+        )
+    }
+}
+
+/// A cached value and when it was fetched.
+#[derive(Debug)]
+pub struct Cached<V> {
+    pub value: V,
+    pub fetched_at: Instant,
+}
+
+impl<V> Cached<V> {
+    /// Time left before the value expires.
+    pub fn remaining(&self, ttl: Duration) -> Duration {
+        ttl.saturating_sub(self.fetched_at.elapsed())
+    }
+}
+
+/// What a fetch gave, the error is shared by every caller waiting on it.
+type Outcome<V> = Result<Arc<Cached<V>>, Arc<anyhow::Error>>;
+type Slot<V> = Arc<OnceCell<Outcome<V>>>;
+
+pub struct TtlCache<V> {
+    ttl: Duration,
+    slots: Mutex<HashMap<CacheKey, Slot<V>>>,
+}
+
+impl<V> TtlCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        TtlCache {
+            ttl,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn is_fresh(&self, slot: &Slot<V>) -> bool {
+        match slot.get() {
+            // a fetch in flight, keep it to join it
+            None => true,
+            Some(Ok(cached)) => cached.fetched_at.elapsed() < self.ttl,
+            Some(Err(_)) => false,
+        }
+    }
+
+    /// The slot for this key, replacing it if it expired.
+    fn slot(&self, key: &CacheKey) -> Slot<V> {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(slot) = slots.get(key).filter(|slot| self.is_fresh(slot)) {
+            return slot.clone();
+        }
+        // drop whatever expired while we hold the lock
+        slots.retain(|_, slot| self.is_fresh(slot));
+        let slot = Slot::default();
+        slots.insert(key.clone(), slot.clone());
+        slot
+    }
+
+    /// Cached value for `key`, or the result of `fetch` (shared with concurrent callers).
+    pub async fn get_or_fetch<F, Fut>(&self, key: &CacheKey, fetch: F) -> Result<Arc<Cached<V>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        let slot = self.slot(key);
+        let outcome = slot
+            .get_or_init(|| async {
+                let value = fetch().await.map_err(Arc::new)?;
+                Ok(Arc::new(Cached {
+                    value,
+                    fetched_at: Instant::now(),
+                }))
+            })
+            .await;
+        match outcome {
+            Ok(cached) => Ok(cached.clone()),
+            Err(e) => {
+                self.forget(key, &slot);
+                Err(anyhow!("{e:#}"))
+            }
+        }
+    }
+
+    /// Drop the slot of a failed fetch, unless it was already replaced.
+    fn forget(&self, key: &CacheKey, slot: &Slot<V>) {
+        let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+        if slots
+            .get(key)
+            .is_some_and(|current| Arc::ptr_eq(current, slot))
+        {
+            slots.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn key() -> CacheKey {
+        CacheKey::new(50.8505, 4.3488, "daily")
+    }
+
+    /// Fetch counting its calls, slow enough for concurrent callers to meet
+    async fn fetch(calls: &AtomicUsize, fail: bool) -> Result<usize> {
+        let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        if fail {
+            anyhow::bail!("upstream down");
+        }
+        Ok(call)
+    }
+
+    fn slots<V>(cache: &TtlCache<V>) -> usize {
+        cache.slots.lock().unwrap().len()
+    }
+
+    #[test]
+    fn keys_are_rounded() {
+        let key = key();
+        assert_eq!(key, CacheKey::new(50.8512, 4.3461, "daily"));
+        assert_ne!(key, CacheKey::new(50.8449, 4.3488, "daily"));
+        assert_ne!(key, CacheKey::new(50.8505, 4.3488, "hourly"));
+        assert_eq!(key.coordinates(), (50.85, 4.35));
+    }
+
+    #[tokio::test]
+    async fn hit_until_expiry() {
+        let key = key();
+        let cache = TtlCache::new(Duration::from_millis(100));
+        let calls = AtomicUsize::new(0);
+
+        let first = cache
+            .get_or_fetch(&key, || fetch(&calls, false))
+            .await
+            .unwrap();
+        let second = cache
+            .get_or_fetch(&key, || fetch(&calls, false))
+            .await
+            .unwrap();
+        assert_eq!((first.value, second.value), (1, 1));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        let third = cache
+            .get_or_fetch(&key, || fetch(&calls, false))
+            .await
+            .unwrap();
+        assert_eq!(third.value, 2);
+        assert_eq!(slots(&cache), 1);
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_share_one_fetch() {
+        let key = key();
+        let cache = TtlCache::new(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        let (a, b, c) = tokio::join!(
+            cache.get_or_fetch(&key, || fetch(&calls, false)),
+            cache.get_or_fetch(&key, || fetch(&calls, false)),
+            cache.get_or_fetch(&key, || fetch(&calls, false)),
+        );
+        assert_eq!(
+            (a.unwrap().value, b.unwrap().value, c.unwrap().value),
+            (1, 1, 1)
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_are_shared_then_forgotten() {
+        let key = key();
+        let cache = TtlCache::<usize>::new(Duration::from_secs(60));
+        let calls = AtomicUsize::new(0);
+
+        let (a, b) = tokio::join!(
+            cache.get_or_fetch(&key, || fetch(&calls, true)),
+            cache.get_or_fetch(&key, || fetch(&calls, true)),
+        );
+        assert!(a.unwrap_err().to_string().contains("upstream down"));
+        assert!(b.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(slots(&cache), 0);
+
+        // the next request tries again
+        let value = cache
+            .get_or_fetch(&key, || fetch(&calls, false))
+            .await
+            .unwrap();
+        assert_eq!(value.value, 2);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::color;
+use crate::display::{RenderOptions, pad_left, truncate, width};
+use crate::models::{City, ForecastResponse};
+
+// Terminal charts for the daily forecast: temperature lines drawn with braille dots
+// (2x4 dots per character, https://en.wikipedia.org/wiki/Braille_Patterns) and
+// precipitation drawn as vertical block bars. ASCII mode uses one marker per character.
+
+/// Height of the temperature plot, in terminal rows.
+const TEMP_ROWS: usize = 12;
+/// Height of the precipitation bars, in terminal rows.
+const RAIN_ROWS: usize = 4;
+/// Don't stretch a day wider than this, the lines get too flat.
+const MAX_DAY_WIDTH: usize = 14;
+/// Below this much rain the bars scale to 1 mm, so drizzle doesn't look like a flood.
+const MIN_RAIN_SCALE: f64 = 1.0;
+
+const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const RAIN_COLOR: (u8, u8, u8) = (70, 130, 230);
+
+/// Bit of each dot inside a braille character, indexed by [y][x].
+const BRAILLE_BITS: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// One line of the chart.
+pub struct Series<'a> {
+    pub name: &'a str,
+    pub values: &'a [f64],
+    pub color: (u8, u8, u8),
+    /// Character used in ASCII mode.
+    pub marker: char,
+}
+
+/// Grid of characters where series are drawn. In unicode mode every cell holds 2x4 dots.
+struct Canvas {
+    cols: usize,
+    rows: usize,
+    unicode: bool,
+    /// Braille dot mask of each cell (or just non-zero in ASCII mode).
+    dots: Vec<u8>,
+    /// Last series drawn in each cell, which gives its colour/marker.
+    owner: Vec<Option<usize>>,
+}
+
+impl Canvas {
+    fn new(cols: usize, rows: usize, unicode: bool) -> Self {
+        Canvas {
+            cols,
+            rows,
+            unicode,
+            dots: vec![0; cols * rows],
+            owner: vec![None; cols * rows],
+        }
+    }
+
+    /// Size in dots.
+    fn resolution(&self) -> (usize, usize) {
+        if self.unicode {
+            (self.cols * 2, self.rows * 4)
+        } else {
+            (self.cols, self.rows)
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize, series: usize) {
+        let (cx, cy, bit) = if self.unicode {
+            (x / 2, y / 4, BRAILLE_BITS[y % 4][x % 2])
+        } else {
+            (x, y, 1)
+        };
+        if cx >= self.cols || cy >= self.rows {
+            return;
+        }
+        let idx = cy * self.cols + cx;
+        self.dots[idx] |= bit;
+        self.owner[idx] = Some(series);
+    }
+
+    /// Straight line between two dots.
+    fn line(&mut self, (x0, y0): (usize, usize), (x1, y1): (usize, usize), series: usize) {
+        let steps = x0.abs_diff(x1).max(y0.abs_diff(y1)).max(1);
+        for i in 0..=steps {
+            let k = i as f64 / steps as f64;
+            let x = x0 as f64 + (x1 as f64 - x0 as f64) * k;
+            let y = y0 as f64 + (y1 as f64 - y0 as f64) * k;
+            self.set(x.round() as usize, y.round() as usize, series);
+        }
+    }
+
+    fn render_row(&self, row: usize, series: &[Series], opts: &RenderOptions) -> String {
+        (0..self.cols)
+            .map(|col| {
+                let idx = row * self.cols + col;
+                match self.owner[idx] {
+                    None => " ".to_string(),
+                    Some(s) => {
+                        let c = if self.unicode {
+                            char::from_u32(0x2800 + self.dots[idx] as u32).unwrap_or(' ')
+                        } else {
+                            series[s].marker
+                        };
+                        color::rgb(&c.to_string(), series[s].color, opts.color)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Print min/max/mean temperature lines and precipitation bars for every forecast day.
+pub fn print_chart(city: &City, forecast: &ForecastResponse, opts: &RenderOptions) {
+    let n = forecast.len();
+    if n == 0 {
+        println!("No forecast data available for {}", city.name);
+        return;
+    }
+    let daily = &forecast.daily;
+    let u = &forecast.daily_units.temperature_2m_min;
+    let series = [
+        Series {
+            name: "mean",
+            values: &daily.temperature_2m_mean,
+            color: (240, 200, 80),
+            marker: '-',
+        },
+        Series {
+            name: "min",
+            values: &daily.temperature_2m_min,
+            color: (90, 160, 255),
+            marker: 'v',
+        },
+        Series {
+            name: "max",
+            values: &daily.temperature_2m_max,
+            color: (255, 110, 70),
+            marker: '^',
+        },
+    ];
+
+    // Axis labels like "-12.3 °C ┤", then the plot takes what's left of the terminal
+    let label_width = width(&format!("{:>6.1} {u}", 0.0));
+    let available = opts.width.saturating_sub(label_width + 2).max(n);
+    let day_width = (available / n).clamp(1, MAX_DAY_WIDTH);
+    let cols = day_width * n;
+    let (axis, tick, corner, line) = if opts.unicode {
+        ("│", "┤", "└", "─")
+    } else {
+        ("|", "+", "+", "-")
+    };
+
+    // Title & legend
+    let dash = if opts.unicode { "—" } else { "-" };
+    println!(
+        "\n {}",
+        color::bold(&format!("{city} {dash} {n} days"), opts.color)
+    );
+    let mut legend: Vec<String> = series
+        .iter()
+        .rev()
+        .map(|s| {
+            let mark = if opts.unicode {
+                "━".to_string()
+            } else {
+                s.marker.to_string()
+            };
+            format!("{} {}", color::rgb(&mark, s.color, opts.color), s.name)
+        })
+        .collect();
+    let bar = if opts.unicode { "▇" } else { "#" };
+    legend.push(format!(
+        "{} precipitation",
+        color::rgb(bar, RAIN_COLOR, opts.color)
+    ));
+    println!(" {}\n", legend.join("   "));
+
+    // Temperature lines
+    let all = series.iter().flat_map(|s| s.values.iter().copied());
+    let (mut lo, mut hi) = all.fold((f64::MAX, f64::MIN), |(lo, hi), t| (lo.min(t), hi.max(t)));
+    if hi - lo < 1.0 {
+        lo -= 0.5;
+        hi += 0.5;
+    }
+    let mut canvas = Canvas::new(cols, TEMP_ROWS, opts.unicode);
+    let (dots_x, dots_y) = canvas.resolution();
+    let dots_per_col = dots_x / cols;
+    let point = |day: usize, t: f64| {
+        let x = (day * day_width + day_width / 2) * dots_per_col;
+        let y = ((hi - t) / (hi - lo) * (dots_y - 1) as f64).round() as usize;
+        (x, y)
+    };
+    for (s, serie) in series.iter().enumerate() {
+        let points: Vec<_> = serie
+            .values
+            .iter()
+            .enumerate()
+            .map(|(day, &t)| point(day, t))
+            .collect();
+        if let [only] = points[..] {
+            canvas.set(only.0, only.1, s);
+        }
+        for pair in points.windows(2) {
+            canvas.line(pair[0], pair[1], s);
+        }
+    }
+    for row in 0..TEMP_ROWS {
+        let value = match row {
+            0 => Some(hi),
+            r if r == TEMP_ROWS / 2 => Some((hi + lo) / 2.0),
+            r if r == TEMP_ROWS - 1 => Some(lo),
+            _ => None,
+        };
+        let label = match value {
+            Some(t) => format!("{} {tick}", pad_left(&format!("{t:.1} {u}"), label_width)),
+            None => format!("{} {axis}", " ".repeat(label_width)),
+        };
+        println!("{label}{}", canvas.render_row(row, &series, opts));
+    }
+
+    // Precipitation bars, in eighths of a row
+    let rain = &daily.precipitation_sum;
+    let rain_max = rain.iter().copied().fold(MIN_RAIN_SCALE, f64::max);
+    let levels = RAIN_ROWS * 8;
+    let bar_width = (day_width * 2 / 3).max(1);
+    let bar_offset = (day_width - bar_width) / 2;
+    let rain_unit = &forecast.daily_units.precipitation_sum;
+    for row in 0..RAIN_ROWS {
+        let from_bottom = RAIN_ROWS - 1 - row;
+        let label = match row {
+            0 => format!(
+                "{} {tick}",
+                pad_left(&format!("{rain_max:.1} {rain_unit}"), label_width)
+            ),
+            r if r == RAIN_ROWS - 1 => {
+                format!(
+                    "{} {tick}",
+                    pad_left(&format!("0 {rain_unit}"), label_width)
+                )
+            }
+            _ => format!("{} {axis}", " ".repeat(label_width)),
+        };
+        let mut bars = String::new();
+        for &p in rain.iter().take(n) {
+            let height = (p / rain_max * levels as f64).round() as usize;
+            let fill = height.saturating_sub(from_bottom * 8).min(8);
+            let c = if opts.unicode {
+                BARS[fill]
+            } else if fill >= 4 {
+                '#'
+            } else {
+                ' '
+            };
+            let cell = c.to_string().repeat(bar_width);
+            bars.push_str(&" ".repeat(bar_offset));
+            bars.push_str(&color::rgb(&cell, RAIN_COLOR, opts.color));
+            bars.push_str(&" ".repeat(day_width - bar_width - bar_offset));
+        }
+        println!("{label}{bars}");
+    }
+
+    // X axis with the dates ("10-18", or just "18" when days are narrow)
+    println!("{} {corner}{}", " ".repeat(label_width), line.repeat(cols));
+    let dates: String = daily
+        .time
+        .iter()
+        .map(|date| {
+            let short = match day_width {
+                w if w >= 6 => date.get(5..).unwrap_or(date),
+                w if w >= 3 => date.get(8..).unwrap_or(date),
+                _ => "",
+            };
+            let short = truncate(short, day_width);
+            let left = (day_width - width(&short)) / 2;
+            format!(
+                "{}{short}{}",
+                " ".repeat(left),
+                " ".repeat(day_width - width(&short) - left)
+            )
+        })
+        .collect();
+    println!("{}  {}", " ".repeat(label_width), dates.trim_end());
+}
@@ -0,0 +1,74 @@
+use crate::args::ColorChoice;
+use std::io::IsTerminal;
+
+// Minimal ANSI colouring, no need for a crate for a handful of escape codes.
+// https://en.wikipedia.org/wiki/ANSI_escape_code#SGR_(Select_Graphic_Rendition)_parameters
+
+const RESET: &str = "\x1b[0m";
+
+/// Cold to hot gradient stops (°C, rgb), linearly interpolated in between.
+const GRADIENT: [(f64, (u8, u8, u8)); 6] = [
+    (-10.0, (90, 90, 255)),
+    (0.0, (80, 170, 255)),
+    (10.0, (90, 220, 200)),
+    (18.0, (140, 220, 90)),
+    (25.0, (255, 200, 60)),
+    (35.0, (255, 60, 40)),
+];
+
+/// Whether colours should be used for this run.
+/// `auto` follows https://no-color.org and turns colours off when stdout is piped.
+pub fn enabled(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }
+    }
+}
+
+fn paint(text: &str, sgr: &str, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{sgr}m{text}{RESET}")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Colour of a temperature on the gradient.
+pub fn gradient(celsius: f64) -> (u8, u8, u8) {
+    let (first_t, first_rgb) = GRADIENT[0];
+    if celsius <= first_t {
+        return first_rgb;
+    }
+    for pair in GRADIENT.windows(2) {
+        let ((t0, c0), (t1, c1)) = (pair[0], pair[1]);
+        if celsius <= t1 {
+            let k = (celsius - t0) / (t1 - t0);
+            let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * k).round() as u8;
+            return (mix(c0.0, c1.0), mix(c0.1, c1.1), mix(c0.2, c1.2));
+        }
+    }
+    GRADIENT[GRADIENT.len() - 1].1
+}
+
+/// Paint `text` with a 24-bit colour.
+pub fn rgb(text: &str, (r, g, b): (u8, u8, u8), enabled: bool) -> String {
+    paint(text, &format!("38;2;{r};{g};{b}"), enabled)
+}
+
+/// Paint `text` with the gradient colour of `celsius`.
+pub fn temperature(text: &str, celsius: f64, enabled: bool) -> String {
+    rgb(text, gradient(celsius), enabled)
+}
+
+/// Bold red, for severe weather.
+pub fn severe(text: &str, enabled: bool) -> String {
+    paint(text, "1;31", enabled)
+}
+
+pub fn bold(text: &str, enabled: bool) -> String {
+    paint(text, "1", enabled)
+}
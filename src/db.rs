@@ -0,0 +1,421 @@
+use crate::alerts::Alert;
+use crate::cities::get_favourite_cities;
+use crate::models::{City, ForecastResponse};
+use crate::normals::Normals;
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs};
+use utoipa::ToSchema;
+
+// Local SQLite database with the cities we synchronize, in the XDG data directory by default.
+// A new database starts with our 10 favourite cities (only once: removing them all sticks).
+// It keeps the last forecasts fetched for each city (versioned snapshots, to see what changed)
+// and remembers which alerts were sent to which notification sink.
+// Climate normals are cached per city, with the coordinates they were computed for.
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cities (
+    id   INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    lat  REAL NOT NULL,
+    long REAL NOT NULL,
+    coastal INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS forecast_snapshots (
+    version    INTEGER PRIMARY KEY,
+    city_id    INTEGER NOT NULL REFERENCES cities (id) ON DELETE CASCADE,
+    fetched_at TEXT NOT NULL,
+    forecast   TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS forecast_snapshots_city ON forecast_snapshots (city_id, version);
+
+CREATE TABLE IF NOT EXISTS climate_normals (
+    city_id     INTEGER PRIMARY KEY REFERENCES cities (id) ON DELETE CASCADE,
+    lat         REAL NOT NULL,
+    long        REAL NOT NULL,
+    computed_at TEXT NOT NULL DEFAULT (datetime('now')),
+    normals     TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS notified_alerts (
+    sink    TEXT NOT NULL,
+    rule    TEXT NOT NULL,
+    city    TEXT NOT NULL,
+    date    TEXT NOT NULL,
+    sent_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (sink, rule, city, date)
+);
+";
+
+/// City saved in the database
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct StoredCity {
+    pub id: i64,
+    #[serde(flatten)]
+    pub city: City,
+}
+
+/// Recorded in `PRAGMA user_version` once the schema is created and the favourites added.
+const SCHEMA_VERSION: i64 = 1;
+
+/// Snapshots older than this are dropped when a new one is saved.
+const SNAPSHOT_RETENTION_DAYS: u32 = 90;
+
+/// A forecast as it was fetched at some point
+#[derive(Debug)]
+pub struct Snapshot {
+    pub version: i64,
+    /// Local time, "YYYY-MM-DD HH:MM:SS"
+    pub fetched_at: String,
+    pub forecast: ForecastResponse,
+}
+
+/// Reasons a city can't be saved, so callers (CLI, API) can tell them apart from db failures.
+#[derive(Debug)]
+pub enum CityError {
+    EmptyName,
+    InvalidCoordinates { lat: f64, long: f64 },
+    Duplicate(String),
+}
+
+impl fmt::Display for CityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CityError::EmptyName => write!(f, "City name can't be empty"),
+            CityError::InvalidCoordinates { lat, long } => write!(
+                f,
+                "Coordinates out of range: lat {lat} (-90 to 90), long {long} (-180 to 180)"
+            ),
+            CityError::Duplicate(name) => write!(f, "'{name}' is already in the city list"),
+        }
+    }
+}
+
+impl std::error::Error for CityError {}
+
+/// Check the name and coordinate ranges of a city.
+pub fn validate(city: &City) -> Result<(), CityError> {
+    if city.name.trim().is_empty() {
+        return Err(CityError::EmptyName);
+    }
+    validate_coordinates(city.lat, city.long)
+}
+
+/// Latitude in -90..=90 and longitude in -180..=180 (NaN is rejected too).
+pub fn validate_coordinates(lat: f64, long: f64) -> Result<(), CityError> {
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&long) {
+        return Err(CityError::InvalidCoordinates { lat, long });
+    }
+    Ok(())
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+/// Database used without `--db`: `$XDG_DATA_HOME/deca-weather/deca-weather.db`, falling back
+/// to `~/.local/share` (and to the current directory without a home).
+pub fn default_path() -> PathBuf {
+    let data_dir = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    match data_dir {
+        Some(dir) => dir.join("deca-weather").join("deca-weather.db"),
+        None => PathBuf::from("deca-weather.db"),
+    }
+}
+
+impl Store {
+    /// Open (or create) the database file.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Couldn't create directory {}", dir.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Couldn't open database {}", path.display()))?;
+        // needed for ON DELETE CASCADE
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        // databases from before the version was recorded already had their favourites
+        let had_cities: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'cities'",
+            [],
+            |row| row.get(0),
+        )?;
+        conn.execute_batch(SCHEMA)
+            .context("Couldn't create database schema")?;
+        let store = Store { conn };
+        store.migrate()?;
+
+        if version < SCHEMA_VERSION {
+            if !had_cities {
+                for city in get_favourite_cities() {
+                    store.add_city(&city)?;
+                }
+            }
+            store
+                .conn
+                .pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        }
+        Ok(store)
+    }
+
+    /// Open the database only if it exists, for commands which just look cities up.
+    pub fn open_existing(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::open(path).map(Some)
+    }
+
+    /// Columns added after the first release, for databases created before them
+    fn migrate(&self) -> Result<()> {
+        let has_coastal: bool = self.conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('cities') WHERE name = 'coastal'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_coastal {
+            self.conn.execute(
+                "ALTER TABLE cities ADD COLUMN coastal INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+            // flag the favourites that are on the sea, if they are still there
+            for city in get_favourite_cities().iter().filter(|city| city.coastal) {
+                self.conn.execute(
+                    "UPDATE cities SET coastal = 1 WHERE name = ?1",
+                    [&city.name],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn city_from_row(row: &Row) -> rusqlite::Result<StoredCity> {
+        Ok(StoredCity {
+            id: row.get("id")?,
+            city: City {
+                name: row.get("name")?,
+                lat: row.get("lat")?,
+                long: row.get("long")?,
+                coastal: row.get("coastal")?,
+            },
+        })
+    }
+
+    pub fn cities(&self) -> Result<Vec<StoredCity>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, lat, long, coastal FROM cities ORDER BY id")?;
+        let cities = stmt
+            .query_map([], Self::city_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(cities)
+    }
+
+    pub fn city(&self, id: i64) -> Result<Option<StoredCity>> {
+        let city = self
+            .conn
+            .query_row(
+                "SELECT id, name, lat, long, coastal FROM cities WHERE id = ?1",
+                [id],
+                Self::city_from_row,
+            )
+            .optional()?;
+        Ok(city)
+    }
+
+    /// Find a city by name (case insensitive)
+    pub fn find_city(&self, name: &str) -> Result<Option<StoredCity>> {
+        let city = self
+            .conn
+            .query_row(
+                "SELECT id, name, lat, long, coastal FROM cities WHERE name = ?1",
+                [name.trim()],
+                Self::city_from_row,
+            )
+            .optional()?;
+        Ok(city)
+    }
+
+    /// Errors with a `CityError` if the city is invalid or its name already taken
+    pub fn add_city(&self, city: &City) -> Result<StoredCity> {
+        validate(city)?;
+        self.check_unique(&city.name, None)?;
+
+        self.conn.execute(
+            "INSERT INTO cities (name, lat, long, coastal) VALUES (?1, ?2, ?3, ?4)",
+            params![city.name.trim(), city.lat, city.long, city.coastal],
+        )?;
+        Ok(StoredCity {
+            id: self.conn.last_insert_rowid(),
+            city: City {
+                name: city.name.trim().to_string(),
+                ..city.clone()
+            },
+        })
+    }
+
+    /// Replace a city, `None` if there is no city with this id
+    pub fn update_city(&self, id: i64, city: &City) -> Result<Option<StoredCity>> {
+        validate(city)?;
+        self.check_unique(&city.name, Some(id))?;
+
+        let changed = self.conn.execute(
+            "UPDATE cities SET name = ?1, lat = ?2, long = ?3, coastal = ?4 WHERE id = ?5",
+            params![city.name.trim(), city.lat, city.long, city.coastal, id],
+        )?;
+        if changed == 0 {
+            return Ok(None);
+        }
+        self.city(id)
+    }
+
+    /// `false` if there was no city with this id
+    pub fn remove_city(&self, id: i64) -> Result<bool> {
+        let changed = self
+            .conn
+            .execute("DELETE FROM cities WHERE id = ?1", [id])?;
+        Ok(changed > 0)
+    }
+
+    /// Save a new version of the forecast of a stored city, returns its version
+    pub fn save_snapshot(&self, city_id: i64, forecast: &ForecastResponse) -> Result<i64> {
+        let fetched_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.conn.execute(
+            "INSERT INTO forecast_snapshots (city_id, fetched_at, forecast) VALUES (?1, ?2, ?3)",
+            params![city_id, fetched_at, serde_json::to_string(forecast)?],
+        )?;
+        let version = self.conn.last_insert_rowid();
+
+        // Thin out: everything from the last day (for diff), then the last one of each day
+        // (for verify), for SNAPSHOT_RETENTION_DAYS
+        self.conn.execute(
+            "DELETE FROM forecast_snapshots WHERE city_id = ?1 AND (
+                fetched_at < datetime('now', 'localtime', ?2)
+                OR (fetched_at < datetime('now', 'localtime', '-1 day')
+                    AND version NOT IN (SELECT MAX(version) FROM forecast_snapshots
+                                        WHERE city_id = ?1 GROUP BY date(fetched_at))))",
+            params![city_id, format!("-{SNAPSHOT_RETENTION_DAYS} days")],
+        )?;
+        Ok(version)
+    }
+
+    /// The `count` latest snapshots of a city, newest first
+    pub fn latest_snapshots(&self, city_id: i64, count: usize) -> Result<Vec<Snapshot>> {
+        self.query_snapshots(
+            "SELECT version, fetched_at, forecast FROM forecast_snapshots
+             WHERE city_id = ?1 ORDER BY version DESC LIMIT ?2",
+            params![city_id, count as i64],
+        )
+    }
+
+    /// Every snapshot kept for a city, oldest first
+    pub fn snapshots(&self, city_id: i64) -> Result<Vec<Snapshot>> {
+        self.query_snapshots(
+            "SELECT version, fetched_at, forecast FROM forecast_snapshots
+             WHERE city_id = ?1 ORDER BY version",
+            params![city_id],
+        )
+    }
+
+    fn query_snapshots(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Snapshot>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt
+            .query_map(params, |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?))
+            })?
+            .collect::<rusqlite::Result<Vec<(i64, String, String)>>>()?;
+
+        rows.into_iter()
+            .map(|(version, fetched_at, json)| {
+                let forecast = serde_json::from_str(&json)
+                    .with_context(|| format!("Corrupted forecast snapshot {version}"))?;
+                Ok(Snapshot {
+                    version,
+                    fetched_at,
+                    forecast,
+                })
+            })
+            .collect()
+    }
+
+    /// Cached normals of a city, unless they are older than `max_age_days` or the city moved
+    pub fn normals(&self, stored: &StoredCity, max_age_days: u32) -> Result<Option<Normals>> {
+        let json: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT normals FROM climate_normals
+                 WHERE city_id = ?1 AND lat = ?2 AND long = ?3 AND computed_at > datetime('now', ?4)",
+                params![
+                    stored.id,
+                    stored.city.lat,
+                    stored.city.long,
+                    format!("-{max_age_days} days")
+                ],
+                |row| row.get(0),
+            )
+            .optional()?;
+        json.map(|json| serde_json::from_str(&json).context("Corrupted climate normals"))
+            .transpose()
+    }
+
+    pub fn save_normals(&self, stored: &StoredCity, normals: &Normals) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO climate_normals (city_id, lat, long, normals)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                stored.id,
+                stored.city.lat,
+                stored.city.long,
+                serde_json::to_string(normals)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Whether `sink` was already sent this alert (same rule, city and date)
+    pub fn was_notified(&self, sink: &str, alert: &Alert) -> Result<bool> {
+        let found = self
+            .conn
+            .query_row(
+                "SELECT 1 FROM notified_alerts WHERE sink = ?1 AND rule = ?2 AND city = ?3 AND date = ?4",
+                params![sink, alert.rule, alert.city, alert.date],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    pub fn mark_notified(&self, sink: &str, alert: &Alert) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO notified_alerts (sink, rule, city, date) VALUES (?1, ?2, ?3, ?4)",
+            params![sink, alert.rule, alert.city, alert.date],
+        )?;
+        Ok(())
+    }
+
+    /// Forget alerts sent more than `days` days ago
+    pub fn prune_notified(&self, days: u32) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM notified_alerts WHERE sent_at < datetime('now', ?1)",
+            [format!("-{days} days")],
+        )?;
+        Ok(())
+    }
+
+    fn check_unique(&self, name: &str, except: Option<i64>) -> Result<()> {
+        match self.find_city(name)? {
+            Some(existing) if Some(existing.id) != except => {
+                Err(CityError::Duplicate(existing.city.name).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::color;
+use crate::display::{RenderOptions, day_label};
+use crate::models::ForecastResponse;
+use crate::wmo::{Category, category, icon};
+
+// What changed between two forecasts of the same place, day by day (matched on the date).
+// Small temperature and precipitation updates are noise and ignored, see `Thresholds`.
+
+/// Smallest changes worth reporting.
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    /// °C, for min and max
+    pub temperature: f64,
+    /// mm
+    pub precipitation: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            temperature: 1.0,
+            precipitation: 1.0,
+        }
+    }
+}
+
+/// Changes of one day, `None` fields didn't change (enough).
+#[derive(Debug)]
+pub struct DayChange {
+    /// Day index in the new forecast
+    pub id: usize,
+    pub date: String,
+    /// Weather codes, when the category changed (cloudy to rain...)
+    pub code: Option<(u32, u32)>,
+    pub min: Option<(f64, f64)>,
+    pub max: Option<(f64, f64)>,
+    pub precipitation: Option<(f64, f64)>,
+    /// Units of the new forecast ("°C", "mm"...)
+    pub temperature_unit: String,
+    pub precipitation_unit: String,
+}
+
+/// Days of `new` that changed since `old`.
+pub fn compare(
+    old: &ForecastResponse,
+    new: &ForecastResponse,
+    limits: Thresholds,
+) -> Vec<DayChange> {
+    let changed = |before: f64, after: f64, limit: f64| {
+        ((after - before).abs() >= limit).then_some((before, after))
+    };
+    let (o, n) = (&old.daily, &new.daily);
+
+    let mut changes = vec![];
+    for (id, date) in n.time.iter().enumerate() {
+        let Some(old_id) = o.time.iter().position(|d| d == date) else {
+            continue;
+        };
+        let (before, after) = (o.weather_code[old_id], n.weather_code[id]);
+        let change = DayChange {
+            id,
+            date: date.clone(),
+            code: (category(before) != category(after)).then_some((before, after)),
+            min: changed(
+                o.temperature_2m_min[old_id],
+                n.temperature_2m_min[id],
+                limits.temperature,
+            ),
+            max: changed(
+                o.temperature_2m_max[old_id],
+                n.temperature_2m_max[id],
+                limits.temperature,
+            ),
+            precipitation: changed(
+                o.precipitation_sum[old_id],
+                n.precipitation_sum[id],
+                limits.precipitation,
+            ),
+            temperature_unit: new.daily_units.temperature_2m_max.clone(),
+            precipitation_unit: new.daily_units.precipitation_sum.clone(),
+        };
+        if change.code.is_some()
+            || change.min.is_some()
+            || change.max.is_some()
+            || change.precipitation.is_some()
+        {
+            changes.push(change);
+        }
+    }
+    changes
+}
+
+impl DayChange {
+    /// "Tomorrow (2026-10-19): cloudy -> rain, max 17.2 -> 14.0 °C (-3.2)"
+    pub fn describe(&self, opts: &RenderOptions) -> String {
+        let arrow = if opts.unicode { "→" } else { "->" };
+        let mut parts = vec![];
+
+        if let Some((before, after)) = self.code {
+            let (from, to) = (category(before), category(after));
+            let text = format!(
+                "{} {from} {arrow} {} {to}",
+                icon(before, opts.unicode),
+                icon(after, opts.unicode)
+            );
+            // getting worse is what people want to notice
+            let worse = matches!(to, Category::Rain | Category::Snow | Category::Thunderstorm);
+            parts.push(if worse {
+                color::severe(&text, opts.color)
+            } else {
+                color::bold(&text, opts.color)
+            });
+        }
+        let temperatures = [("min", self.min), ("max", self.max)];
+        for (name, change) in temperatures {
+            if let Some((before, after)) = change {
+                parts.push(format!(
+                    "{name} {} {arrow} {} {} ({:+.1})",
+                    color::temperature(&format!("{before:.1}"), before, opts.color),
+                    color::temperature(&format!("{after:.1}"), after, opts.color),
+                    self.temperature_unit,
+                    after - before
+                ));
+            }
+        }
+        if let Some((before, after)) = self.precipitation {
+            parts.push(format!(
+                "rain {before:.1} {arrow} {after:.1} {} ({:+.1})",
+                self.precipitation_unit,
+                after - before
+            ));
+        }
+
+        format!(
+            "{} ({}): {}",
+            day_label(self.id),
+            self.date,
+            parts.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ForecastBuilder;
+
+    #[test]
+    fn days_are_matched_on_their_date() {
+        // fetched a day later: the old forecast starts one day earlier
+        let old = ForecastBuilder::default()
+            .day("2026-10-17", 3, 5.0, 12.0)
+            .day("2026-10-18", 3, 6.0, 14.0)
+            .day("2026-10-19", 3, 7.0, 15.0)
+            .build();
+        let new = ForecastBuilder::default()
+            .day("2026-10-18", 3, 6.0, 14.0)
+            .day("2026-10-19", 61, 7.0, 12.0)
+            .rain(4.0)
+            .day("2026-10-20", 95, 9.0, 13.0)
+            .rain(20.0)
+            .build();
+        let changes = compare(&old, &new, Thresholds::default());
+        assert_eq!(changes.len(), 1);
+        let change = &changes[0];
+        assert_eq!((change.id, change.date.as_str()), (1, "2026-10-19"));
+        assert_eq!(change.code, Some((3, 61)));
+        assert_eq!(change.min, None);
+        assert_eq!(change.max, Some((15.0, 12.0)));
+        assert_eq!(change.precipitation, Some((0.0, 4.0)));
+    }
+
+    #[test]
+    fn small_changes_are_noise() {
+        let old = ForecastBuilder::default()
+            .day("2026-10-18", 2, 6.0, 14.0)
+            .rain(0.5)
+            .build();
+        // another cloudy code, less than a degree and a millimetre
+        let new = ForecastBuilder::default()
+            .day("2026-10-18", 3, 6.9, 13.1)
+            .rain(1.4)
+            .build();
+        assert!(compare(&old, &new, Thresholds::default()).is_empty());
+
+        let strict = Thresholds {
+            temperature: 0.5,
+            precipitation: 0.5,
+        };
+        let change = &compare(&old, &new, strict)[0];
+        assert_eq!(change.code, None);
+        assert_eq!(change.min, Some((6.0, 6.9)));
+        assert_eq!(change.max, Some((14.0, 13.1)));
+        assert_eq!(change.precipitation, Some((0.5, 1.4)));
+    }
+
+    #[test]
+    fn describe_lists_what_changed() {
+        let old = ForecastBuilder::default()
+            .day("2026-10-18", 1, 6.0, 17.2)
+            .build();
+        let new = ForecastBuilder::default()
+            .day("2026-10-18", 61, 6.0, 14.0)
+            .build();
+        let opts = RenderOptions {
+            unicode: false,
+            width: 80,
+            color: false,
+            past: false,
+        };
+        assert_eq!(
+            compare(&old, &new, Thresholds::default())[0].describe(&opts),
+            "Today (2026-10-18): [cloud] clear -> [rain] rain, max 17.2 -> 14.0 °C (-3.2)"
+        );
+    }
+
+    #[test]
+    fn describe_uses_the_units_of_the_forecast() {
+        let fahrenheit = |max: f64, rain: f64| {
+            ForecastBuilder::default()
+                .units("°F", "inch")
+                .day("2026-10-18", 3, 40.0, max)
+                .rain(rain)
+                .build()
+        };
+        let opts = RenderOptions {
+            unicode: false,
+            width: 80,
+            color: false,
+            past: false,
+        };
+        let change = &compare(
+            &fahrenheit(60.0, 0.0),
+            &fahrenheit(55.0, 1.5),
+            Thresholds::default(),
+        )[0];
+        assert_eq!(
+            change.describe(&opts),
+            "Today (2026-10-18): max 60.0 -> 55.0 °F (-5.0), rain 0.0 -> 1.5 inch (+1.5)"
+        );
+    }
+}
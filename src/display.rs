@@ -0,0 +1,222 @@
+use crate::args::ColorChoice;
+use crate::color;
+use chrono::NaiveDate;
+use terminal_size::{Width, terminal_size};
+use unicode_width::UnicodeWidthStr;
+
+// Helpers for terminal layout. Byte length (`len()`) and char count (`chars().count()`)
+// are both wrong for accented names and emoji, so everything goes through the display width.
+
+/// Width used when we can't ask the terminal (piped output, CI...).
+const DEFAULT_TERMINAL_WIDTH: usize = 80;
+
+/// How the output should be drawn, decided once from the args and the terminal.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// Box drawing characters & emoji, or plain ASCII.
+    pub unicode: bool,
+    /// Available columns.
+    pub width: usize,
+    /// ANSI colours on or off.
+    pub color: bool,
+    /// The days are past weather: label them by weekday instead of Today, Tomorrow...
+    pub past: bool,
+}
+
+impl RenderOptions {
+    pub fn detect(no_unicode: bool, color: ColorChoice) -> Self {
+        RenderOptions {
+            unicode: !no_unicode,
+            width: terminal_width(),
+            color: color::enabled(color),
+            past: false,
+        }
+    }
+
+    pub fn box_chars(&self) -> &'static BoxChars {
+        if self.unicode {
+            &UNICODE_BOX
+        } else {
+            &ASCII_BOX
+        }
+    }
+}
+
+/// Characters used to draw boxes, lines and table separators.
+#[derive(Debug)]
+pub struct BoxChars {
+    pub top_left: &'static str,
+    pub top_right: &'static str,
+    pub bottom_left: &'static str,
+    pub bottom_right: &'static str,
+    pub thick: &'static str,
+    pub side: &'static str,
+    pub line: &'static str,
+    pub column: &'static str,
+    pub cross: &'static str,
+}
+
+const UNICODE_BOX: BoxChars = BoxChars {
+    top_left: "╔",
+    top_right: "╗",
+    bottom_left: "╚",
+    bottom_right: "╝",
+    thick: "═",
+    side: "║",
+    line: "─",
+    column: "│",
+    cross: "┼",
+};
+
+const ASCII_BOX: BoxChars = BoxChars {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    thick: "=",
+    side: "|",
+    line: "-",
+    column: "|",
+    cross: "+",
+};
+
+/// Terminal width from the tty, then the COLUMNS variable, then a default.
+pub fn terminal_width() -> usize {
+    if let Some((Width(w), _)) = terminal_size() {
+        return w as usize;
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&c| c > 0)
+        .unwrap_or(DEFAULT_TERMINAL_WIDTH)
+}
+
+/// Number of terminal columns the string takes (colour escape codes take none).
+pub fn width(s: &str) -> usize {
+    if s.contains(ESC) {
+        strip_ansi(s).width()
+    } else {
+        s.width()
+    }
+}
+
+const ESC: char = '\x1b';
+
+/// Remove ANSI escape sequences (`ESC [ ... letter`) from `s`.
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == ESC {
+            // skip until the final byte of the sequence
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Pad `s` with spaces on the right up to `target` columns.
+pub fn pad_right(s: &str, target: usize) -> String {
+    let spaces = target.saturating_sub(width(s));
+    format!("{s}{}", " ".repeat(spaces))
+}
+
+/// Pad `s` with spaces on the left up to `target` columns.
+pub fn pad_left(s: &str, target: usize) -> String {
+    let spaces = target.saturating_sub(width(s));
+    format!("{}{s}", " ".repeat(spaces))
+}
+
+/// Cut `s` so it fits in `target` columns, ending with an ellipsis when cut.
+/// Escape sequences are kept, and reset if the cut happens after one.
+pub fn truncate(s: &str, target: usize) -> String {
+    if width(s) <= target {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut chars = s.chars();
+    let mut styled = false;
+    while let Some(c) = chars.next() {
+        if c == ESC {
+            styled = true;
+            out.push(c);
+            for c in chars.by_ref() {
+                out.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        // measure the whole prefix so emoji + variation selector count as one wide char
+        out.push(c);
+        if width(&out) + 1 > target {
+            out.pop();
+            break;
+        }
+    }
+    out.push('…');
+    if styled {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+/// Word-wrap `s` into lines of at most `target` columns (long words get truncated).
+pub fn wrap(s: &str, target: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    for word in s.split_whitespace() {
+        if !current.is_empty() && width(&current) + 1 + width(word) > target {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        if width(&current) > target {
+            lines.push(truncate(&current, target));
+            current.clear();
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Put blocks of lines next to each other, each block padded to `block_width`.
+pub fn side_by_side(blocks: &[Vec<String>], block_width: usize, separator: &str) -> Vec<String> {
+    let height = blocks.iter().map(Vec::len).max().unwrap_or(0);
+    (0..height)
+        .map(|row| {
+            let cells: Vec<String> = blocks
+                .iter()
+                .map(|block| pad_right(block.get(row).map_or("", String::as_str), block_width))
+                .collect();
+            cells.join(separator).trim_end().to_string()
+        })
+        .collect()
+}
+
+/// Label of a day in cards and tables: `day_label` for forecasts, the weekday for past weather.
+pub fn label_for(id: usize, date: &str, opts: &RenderOptions) -> String {
+    if !opts.past {
+        return day_label(id);
+    }
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|date| date.format("%a").to_string())
+        .unwrap_or_else(|_| day_label(id))
+}
+
+/// Label for a forecast day index (Today, Tomorrow, D+2 etc).
+pub fn day_label(id: usize) -> String {
+    match id {
+        0 => "Today".to_string(),
+        1 => "Tomorrow".to_string(),
+        n => format!("D+{n}"),
+    }
+}
@@ -0,0 +1,165 @@
+use crate::models::{EnsembleDay, EnsembleResponse, ForecastResponse, Percentiles};
+
+// Ensemble forecasts: the same model run many times with slightly different starting
+// conditions. Where the members agree the forecast is reliable, where they spread it isn't.
+// Each day is summed up as percentiles of the members (p10 to p90 is where 80% of them are).
+
+/// Daily precipitation counted as rain for the rain chance (mm).
+const RAIN_THRESHOLD: f64 = 0.1;
+
+/// Percentile `p` (0 to 100) of sorted values, interpolated between the closest two.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+/// p10, p50 and p90 of the values which are numbers, `None` without any.
+fn percentiles(values: &[f64]) -> Option<Percentiles> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+    Some(Percentiles {
+        p10: percentile(&sorted, 10.0),
+        p50: percentile(&sorted, 50.0),
+        p90: percentile(&sorted, 90.0),
+    })
+}
+
+/// Values of every member for `variable` on day `id` (control run included), without the
+/// members which have none.
+fn member_values(ensemble: &EnsembleResponse, variable: &str, id: usize) -> Vec<f64> {
+    let member_prefix = format!("{variable}_member");
+    ensemble
+        .daily
+        .members
+        .iter()
+        .filter(|(name, _)| *name == variable || name.starts_with(&member_prefix))
+        .filter_map(|(_, values)| *values.get(id)?)
+        .filter(|value| !value.is_nan())
+        .collect()
+}
+
+/// Each day of the ensemble forecast with its date, `None` if a variable has no member.
+pub fn daily(ensemble: &EnsembleResponse) -> Vec<(String, Option<EnsembleDay>)> {
+    ensemble
+        .daily
+        .time
+        .iter()
+        .enumerate()
+        .map(|(id, date)| {
+            let min = member_values(ensemble, "temperature_2m_min", id);
+            let max = member_values(ensemble, "temperature_2m_max", id);
+            let precipitation = member_values(ensemble, "precipitation_sum", id);
+            let (Some(temperature_min), Some(temperature_max), Some(spread)) = (
+                percentiles(&min),
+                percentiles(&max),
+                percentiles(&precipitation),
+            ) else {
+                return (date.clone(), None);
+            };
+
+            let rainy = precipitation
+                .iter()
+                .filter(|&&mm| mm >= RAIN_THRESHOLD)
+                .count();
+            let day = EnsembleDay {
+                members: max.len(),
+                temperature_min,
+                temperature_max,
+                precipitation: spread,
+                rain_chance: rainy as f64 * 100.0 / precipitation.len() as f64,
+            };
+            (date.clone(), Some(day))
+        })
+        .collect()
+}
+
+/// Set the ensemble spread of each forecast day (`None` past the ensemble forecast).
+pub fn annotate(forecast: &mut ForecastResponse, ensemble: &EnsembleResponse) {
+    let days = daily(ensemble);
+    let spread = forecast
+        .daily
+        .time
+        .iter()
+        .map(|date| {
+            days.iter()
+                .find(|(day, _)| day == date)
+                .and_then(|(_, spread)| *spread)
+        })
+        .collect();
+    forecast.ensemble = Some(spread);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn no_values_no_percentiles() {
+        assert!(percentiles(&[]).is_none());
+        assert!(percentiles(&[f64::NAN, f64::NAN]).is_none());
+    }
+
+    #[test]
+    fn single_member_is_every_percentile() {
+        let single = percentiles(&[12.5]).unwrap();
+        assert_eq!((single.p10, single.p50, single.p90), (12.5, 12.5, 12.5));
+    }
+
+    #[test]
+    fn percentiles_are_interpolated() {
+        // unsorted, NaN ignored
+        let values = [
+            9.0,
+            f64::NAN,
+            0.0,
+            5.0,
+            1.0,
+            8.0,
+            2.0,
+            7.0,
+            3.0,
+            6.0,
+            4.0,
+            10.0,
+        ];
+        let spread = percentiles(&values).unwrap();
+        assert_eq!((spread.p10, spread.p50, spread.p90), (1.0, 5.0, 9.0));
+
+        let two = percentiles(&[10.0, 20.0]).unwrap();
+        assert_eq!((two.p10, two.p50, two.p90), (11.0, 15.0, 19.0));
+    }
+
+    #[test]
+    fn null_members_are_left_out() {
+        let ensemble: EnsembleResponse = serde_json::from_value(json!({
+            "daily": {
+                "time": ["2026-10-18", "2026-10-19"],
+                "temperature_2m_min": [5.0, null],
+                "temperature_2m_min_member01": [7.0, null],
+                "temperature_2m_min_member02": [null, null],
+                "temperature_2m_max": [14.0, null],
+                "temperature_2m_max_member01": [16.0, null],
+                "temperature_2m_max_member02": [null, null],
+                "precipitation_sum": [0.0, null],
+                "precipitation_sum_member01": [2.0, null],
+                "precipitation_sum_member02": [null, null]
+            }
+        }))
+        .unwrap();
+
+        let days = daily(&ensemble);
+        let day = days[0].1.unwrap();
+        assert_eq!(day.members, 2);
+        assert_eq!(day.temperature_min.p50, 6.0);
+        assert_eq!(day.temperature_max.p50, 15.0);
+        assert_eq!(day.rain_chance, 50.0);
+        // past the forecast every member is null
+        assert_eq!(days[1].0, "2026-10-19");
+        assert!(days[1].1.is_none());
+    }
+}
@@ -1,5 +1,30 @@
+pub mod air;
+pub mod alerts;
 pub mod args;
+pub mod bike;
+pub mod cache;
+pub mod chart;
 pub mod cities;
+pub mod color;
+pub mod db;
+pub mod diff;
+pub mod display;
+pub mod ensemble;
+pub mod marine;
+pub mod metrics;
 pub mod models;
+pub mod normals;
+pub mod notify;
 pub mod open_meteo;
+pub mod rain;
+pub mod server;
+pub mod sun;
+pub mod table;
+#[cfg(test)]
+mod testing;
+pub mod tui;
+pub mod verify;
+pub mod watch;
+pub mod web;
+pub mod wind;
 pub mod wmo;
@@ -1,20 +1,115 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use chrono::Local;
 use clap::Parser;
-use deca_weather::args::{Commands, GetArgs, WeatherArgs};
-use deca_weather::cities::get_favourite_cities;
-use deca_weather::models::City;
-use deca_weather::open_meteo::{get_city_forecast, get_geocode};
+use deca_weather::air;
+use deca_weather::alerts::{self, Alert};
+use deca_weather::args::{
+    AddArgs, AirArgs, AlertsArgs, AlertsCommand, ChartArgs, Commands, CompareModelsArgs, DiffArgs,
+    ExporterArgs, GetArgs, HistoryArgs, ListArgs, MarineArgs, RainArgs, RemoveArgs, ServeArgs,
+    SunArgs, TuiArgs, VerifyArgs, WatchArgs, WeatherArgs, WeatherModel,
+};
+use deca_weather::bike::{self, BIKE_HOURS};
+use deca_weather::chart::print_chart;
+use deca_weather::color;
+use deca_weather::db::{self, Store, StoredCity};
+use deca_weather::diff::{self, Thresholds};
+use deca_weather::display::RenderOptions;
+use deca_weather::ensemble;
+use deca_weather::marine;
+use deca_weather::metrics;
+use deca_weather::models::{City, ForecastResponse};
+use deca_weather::normals::{self, Normals};
+use deca_weather::notify;
+use deca_weather::open_meteo::{
+    DEFAULT_FORECAST_DAYS, MAX_AIR_QUALITY_DAYS, MAX_ENSEMBLE_DAYS, MAX_MARINE_DAYS,
+    get_city_air_quality, get_city_ensemble, get_city_forecast, get_city_forecast_model,
+    get_city_forecast_models, get_city_history, get_city_hourly_forecast, get_city_marine,
+    get_geocode, latest_archive_day,
+};
+use deca_weather::rain;
+use deca_weather::server;
+use deca_weather::sun;
+use deca_weather::table::{print_comparison_table, print_forecast_table, sort_rows};
+use deca_weather::tui;
+use deca_weather::verify;
+use deca_weather::watch::{self, WatchOptions};
+use std::time::Duration;
+
+/// Exit status of `alerts check` when an alert fires, errors exit with 1.
+const ALERTS_FIRED_EXIT_CODE: i32 = 2;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = WeatherArgs::parse();
+    let opts = RenderOptions::detect(args.no_unicode, args.color);
+    let db = args.db.clone().unwrap_or_else(db::default_path);
+    // commands which only look a city up don't create the database
+    let store = || Store::open(&db);
+    let lookup = || Store::open_existing(&db);
 
     match args.command {
-        None | Some(Commands::List) => {
-            handle_list().await?;
+        None => {
+            handle_list(&store()?, &ListArgs::default(), &opts).await?;
+        }
+        Some(Commands::List(list_args)) => {
+            handle_list(&store()?, &list_args, &opts).await?;
         }
         Some(Commands::Get(get_args)) => {
-            handle_get(&get_args).await?;
+            handle_get(&store()?, &get_args, &opts).await?;
+        }
+        Some(Commands::Cities) => {
+            handle_cities(&store()?)?;
+        }
+        Some(Commands::Add(add_args)) => {
+            handle_add(&store()?, &add_args).await?;
+        }
+        Some(Commands::Remove(remove_args)) => {
+            handle_remove(&store()?, &remove_args)?;
+        }
+        Some(Commands::Chart(chart_args)) => {
+            handle_chart(lookup()?.as_ref(), &chart_args, &opts).await?;
+        }
+        Some(Commands::Tui(tui_args)) => {
+            handle_tui(store()?, &tui_args).await?;
+        }
+        Some(Commands::Serve(serve_args)) => {
+            handle_serve(store()?, &serve_args).await?;
+        }
+        Some(Commands::Exporter(exporter_args)) => {
+            handle_exporter(store()?, &exporter_args).await?;
+        }
+        Some(Commands::Alerts(alerts_args)) => {
+            if handle_alerts(&store()?, &alerts_args, &opts).await? {
+                // alerts fired, make it visible to scripts and cron
+                std::process::exit(ALERTS_FIRED_EXIT_CODE);
+            }
+        }
+        Some(Commands::Watch(watch_args)) => {
+            handle_watch(&store()?, &watch_args, &opts).await?;
+        }
+        Some(Commands::Diff(diff_args)) => {
+            handle_diff(&store()?, &diff_args, &opts).await?;
+        }
+        Some(Commands::History(history_args)) => {
+            handle_history(lookup()?.as_ref(), &history_args, &opts).await?;
+        }
+        Some(Commands::Verify(verify_args)) => {
+            handle_verify(&store()?, &verify_args, &opts).await?;
+        }
+        Some(Commands::Air(air_args)) => {
+            handle_air(lookup()?.as_ref(), &air_args, &opts).await?;
+        }
+        Some(Commands::Marine(marine_args)) => {
+            handle_marine(lookup()?.as_ref(), &marine_args, &opts).await?;
+        }
+        Some(Commands::CompareModels(compare_args)) => {
+            handle_compare_models(lookup()?.as_ref(), &compare_args, &opts).await?;
+        }
+        Some(Commands::Rain(rain_args)) => {
+            handle_rain(lookup()?.as_ref(), &rain_args, &opts).await?;
+        }
+        Some(Commands::Sun(sun_args)) => {
+            handle_sun(lookup()?.as_ref(), &sun_args, &opts).await?;
         }
     }
 
@@ -22,53 +117,512 @@ async fn main() -> Result<()> {
 }
 
 /// Task 1: Prints the daily forecast of our 10 favourite Belgian cities
-async fn handle_list() -> Result<()> {
+async fn handle_list(store: &Store, args: &ListArgs, opts: &RenderOptions) -> Result<()> {
     println!("Daily weather for your favourite Belgian cities: \n");
+    let days = selected_days(args.tomorrow, args.day_after);
 
-    for city in get_favourite_cities() {
-        let forecast = get_city_forecast(&city).await?;
-        // we just want today so [0]
-        forecast.print_days_for_city(&city, &[0]);
-        println!();
+    if !args.table {
+        let cities = store.cities()?;
+        // the first time, the archive of every city is asked at once
+        let normals = normals::for_cities(store, &cities).await;
+        for (stored, normals) in cities.iter().zip(normals) {
+            let mut forecast =
+                get_city_forecast_model(&stored.city, DEFAULT_FORECAST_DAYS, args.model).await?;
+            if args.model == WeatherModel::BestMatch {
+                store.save_snapshot(stored.id, &forecast)?;
+            }
+            annotate_normals(normals, &mut forecast);
+            if args.air {
+                add_air_quality(&stored.city, &mut forecast).await;
+            }
+            if stored.city.coastal {
+                add_marine(&stored.city, &mut forecast).await;
+            }
+            if args.ensemble {
+                add_ensemble(&stored.city, &mut forecast).await;
+            }
+            forecast.print_days_for_city(&stored.city, &days, opts);
+            println!();
+        }
+        return Ok(());
+    }
+
+    // Table view: fetch everything first, then sort and print in one go
+    let mut rows = vec![];
+    for StoredCity { id, city } in store.cities()? {
+        let forecast = get_city_forecast_model(&city, DEFAULT_FORECAST_DAYS, args.model).await?;
+        if args.model == WeatherModel::BestMatch {
+            store.save_snapshot(id, &forecast)?;
+        }
+        rows.push((city, forecast));
     }
+    if let Some(sort) = args.sort {
+        sort_rows(&mut rows, sort, days[0]);
+    }
+    print_comparison_table(&rows, &days, opts);
     Ok(())
 }
 
 /// Task 2: Get forecast (optionally for tomorrow and the day after) for your favourite city
-async fn handle_get(args: &GetArgs) -> Result<()> {
+async fn handle_get(store: &Store, args: &GetArgs, opts: &RenderOptions) -> Result<()> {
     println!(
         "Get forecast (optionally for tomorrow and the day after) for your favourite city: \n"
     );
+    let days = selected_days(args.tomorrow, args.day_after);
+    let city = get_city(Some(store), &args.city).await?;
+    let mut forecast = get_city_forecast_model(&city, DEFAULT_FORECAST_DAYS, args.model).await?;
+    if let Some(stored) = store.find_city(&city.name)? {
+        // snapshots are kept to follow one forecast over time, not to mix models
+        if args.model == WeatherModel::BestMatch {
+            store.save_snapshot(stored.id, &forecast)?;
+        }
+        add_normals(store, &stored, &mut forecast).await;
+    }
+    if args.air {
+        add_air_quality(&city, &mut forecast).await;
+    }
+    if city.coastal {
+        add_marine(&city, &mut forecast).await;
+    }
+    if args.ensemble {
+        add_ensemble(&city, &mut forecast).await;
+    }
+
+    forecast.print_days_for_city(&city, &days, opts);
+    println!();
+    if args.bike {
+        let hourly = get_city_hourly_forecast(&city, BIKE_HOURS).await?;
+        bike::print_bike(&hourly, opts);
+        println!();
+    }
+    Ok(())
+}
+
+/// Compare the forecast of a stored city with its climate normals, a failure only costs the
+/// annotation
+async fn add_normals(store: &Store, stored: &StoredCity, forecast: &mut ForecastResponse) {
+    annotate_normals(normals::for_city(store, stored).await, forecast);
+}
+
+fn annotate_normals(normals: Result<Normals>, forecast: &mut ForecastResponse) {
+    match normals {
+        Ok(normals) => normals.annotate(forecast),
+        Err(e) => eprintln!("No climate normals: {e:#}"),
+    }
+}
+
+/// Add the daily air quality index to the cards, a failure only costs the annotation
+async fn add_air_quality(city: &City, forecast: &mut ForecastResponse) {
+    let days = (forecast.len() as u8).clamp(1, MAX_AIR_QUALITY_DAYS);
+    match get_city_air_quality(city, days).await {
+        Ok(air) => air::annotate(forecast, &air),
+        Err(e) => eprintln!("No air quality: {e:#}"),
+    }
+}
+
+/// Add the sea conditions to the cards of a coastal city, a failure only costs the annotation
+async fn add_marine(city: &City, forecast: &mut ForecastResponse) {
+    let days = (forecast.len() as u8).clamp(1, MAX_MARINE_DAYS);
+    match get_city_marine(city, days).await {
+        Ok(sea) => marine::annotate(forecast, &sea),
+        Err(e) => eprintln!("No marine forecast: {e:#}"),
+    }
+}
+
+/// Add the spread of the ensemble members to the cards, a failure only costs the annotation
+async fn add_ensemble(city: &City, forecast: &mut ForecastResponse) {
+    let days = (forecast.len() as u8).clamp(1, MAX_ENSEMBLE_DAYS);
+    match get_city_ensemble(city, days).await {
+        Ok(members) => ensemble::annotate(forecast, &members),
+        Err(e) => eprintln!("No ensemble forecast: {e:#}"),
+    }
+}
+
+/// Task 3: List the cities stored in the database
+fn handle_cities(store: &Store) -> Result<()> {
+    for StoredCity { id, city } in store.cities()? {
+        let coastal = if city.coastal { "  coastal" } else { "" };
+        println!("{id:>4}  {city}{coastal}");
+    }
+    Ok(())
+}
+
+/// Task 3: Add a new city to synchronize
+async fn handle_add(store: &Store, args: &AddArgs) -> Result<()> {
+    let city = match (args.lat, args.long) {
+        (Some(lat), Some(long)) => City {
+            name: args.city.trim().to_string(),
+            lat,
+            long,
+            coastal: args.coastal,
+        },
+        _ => City {
+            coastal: args.coastal,
+            ..City::try_from(get_geocode(&args.city).await?)?
+        },
+    };
+
+    let stored = store.add_city(&city)?;
+    println!("Added {} (id {})", stored.city, stored.id);
+    Ok(())
+}
+
+/// Task 3: Remove a city from the database
+fn handle_remove(store: &Store, args: &RemoveArgs) -> Result<()> {
+    let Some(stored) = store.find_city(&args.city)? else {
+        bail!("'{}' is not in the city list", args.city);
+    };
+    store.remove_city(stored.id)?;
+    println!("Removed {}", stored.city);
+    Ok(())
+}
+
+/// Chart the temperature & precipitation trends of a city
+async fn handle_chart(store: Option<&Store>, args: &ChartArgs, opts: &RenderOptions) -> Result<()> {
+    let city = get_city(store, &args.city).await?;
+    let forecast = get_city_forecast_model(&city, args.days, args.model).await?;
+
+    print_chart(&city, &forecast, opts);
+    println!();
+    Ok(())
+}
+
+/// The forecast of several weather models side by side, one row per model
+async fn handle_compare_models(
+    store: Option<&Store>,
+    args: &CompareModelsArgs,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let city = get_city(store, &args.city).await?;
+    let forecasts = get_city_forecast_models(&city, args.days, &args.models).await?;
+    if forecasts.len() < args.models.len() {
+        let missing: Vec<String> = args
+            .models
+            .iter()
+            .filter(|model| !forecasts.iter().any(|(m, _)| m == *model))
+            .map(ToString::to_string)
+            .collect();
+        eprintln!("No forecast for {}: {}", city.name, missing.join(", "));
+    }
+
+    println!(
+        "{}\n",
+        color::bold(&format!("Weather models for {city}"), opts.color)
+    );
+    let names: Vec<String> = forecasts
+        .iter()
+        .map(|(model, _)| model.to_string())
+        .collect();
+    let rows: Vec<(&str, &ForecastResponse)> = names
+        .iter()
+        .zip(&forecasts)
+        .map(|(name, (_, forecast))| (name.as_str(), forecast))
+        .collect();
+    let days: Vec<usize> = (0..usize::from(args.days)).collect();
+    print_forecast_table("Model", &rows, &days, opts);
+    println!();
+    Ok(())
+}
+
+/// Windows of likely rain over the next hours
+async fn handle_rain(store: Option<&Store>, args: &RainArgs, opts: &RenderOptions) -> Result<()> {
+    let city = get_city(store, &args.city).await?;
+    let forecast = get_city_hourly_forecast(&city, args.hours).await?;
+
+    println!(
+        "\n{}\n",
+        color::bold(
+            &format!("Rain in {city} over the next {} h", args.hours),
+            opts.color
+        )
+    );
+    rain::print_rain(&forecast, opts)?;
+    println!();
+    Ok(())
+}
+
+/// Sunrise, sunset and daylight, day by day
+async fn handle_sun(store: Option<&Store>, args: &SunArgs, opts: &RenderOptions) -> Result<()> {
+    let city = get_city(store, &args.city).await?;
+    let forecast = get_city_forecast_model(&city, args.days, args.model).await?;
+
+    println!(
+        "\n{}\n",
+        color::bold(
+            &format!("Sun in {city}, times in {}", forecast.timezone),
+            opts.color
+        )
+    );
+    sun::print_sun(&forecast, opts)?;
+    println!();
+    Ok(())
+}
+
+/// Full-screen dashboard of the stored cities
+async fn handle_tui(store: Store, args: &TuiArgs) -> Result<()> {
+    let refresh = Duration::from_secs(args.refresh * 60);
+    tui::run(store, refresh).await
+}
+
+/// HTTP API, see the server module for the routes
+async fn handle_serve(store: Store, args: &ServeArgs) -> Result<()> {
+    let state = server::AppState::new(store, Duration::from_secs(args.cache_ttl));
+    server::serve(state, args.addr).await
+}
+
+/// Prometheus metrics of the stored cities, see the metrics module
+async fn handle_exporter(store: Store, args: &ExporterArgs) -> Result<()> {
+    let refresh = Duration::from_secs(args.refresh * 60);
+    metrics::run_exporter(store, args.addr, refresh).await
+}
+
+/// Evaluate the alert rules, `true` if any alert fired
+async fn handle_alerts(store: &Store, args: &AlertsArgs, opts: &RenderOptions) -> Result<bool> {
+    let config = alerts::load(&args.rules)?;
+    match &args.command {
+        AlertsCommand::Check(check) => {
+            let forecasts = alerts::fetch_forecasts(store, &config.rules, false).await?;
+            for e in &forecasts.failed {
+                eprintln!("Not checked: {e:#}");
+            }
+            let fired = alerts::evaluate(&config.rules, &forecasts.fetched);
+            if fired.is_empty() {
+                // a city which couldn't be checked isn't a quiet one
+                if !forecasts.failed.is_empty() {
+                    bail!("{} cities couldn't be checked", forecasts.failed.len());
+                }
+                println!("No alert ({} rules checked)", config.rules.len());
+            }
+            let mark = if opts.unicode { "⚠" } else { "!" };
+            for alert in &fired {
+                println!("{}", color::severe(&format!("{mark} {alert}"), opts.color));
+            }
+
+            if check.notify && !fired.is_empty() {
+                let notified = notify::notify(store, &config.sinks, &fired).await?;
+                println!("Notified {notified} of {} sinks", config.sinks.len());
+            }
+            Ok(!fired.is_empty())
+        }
+        AlertsCommand::TestSinks => {
+            if config.sinks.is_empty() {
+                bail!("No [[sink]] in {}", args.rules.display());
+            }
+            let sample = Alert {
+                rule: "Test alert".to_string(),
+                city: "Brussels".to_string(),
+                day: 0,
+                date: "today".to_string(),
+                detail: "sent by deca-weather alerts test-sinks".to_string(),
+            };
+            for sink in &config.sinks {
+                sink.send(std::slice::from_ref(&sample))
+                    .await
+                    .with_context(|| format!("Sink '{}' failed", sink.name()))?;
+                println!("Sink '{}': sent", sink.name());
+            }
+            Ok(false)
+        }
+    }
+}
+
+/// Refresh, show and check alerts on a schedule until SIGINT/SIGTERM
+async fn handle_watch(store: &Store, args: &WatchArgs, opts: &RenderOptions) -> Result<()> {
+    let alerts = match &args.rules {
+        Some(path) => Some(alerts::load(path)?),
+        None => None,
+    };
+    let options = WatchOptions {
+        every: Duration::from_secs(args.every * 60),
+        log: args.log,
+        alerts,
+        notify: args.notify,
+    };
+    watch::run(store, options, opts).await
+}
+
+/// What changed in the forecast of a stored city since the previous snapshot
+async fn handle_diff(store: &Store, args: &DiffArgs, opts: &RenderOptions) -> Result<()> {
+    let Some(StoredCity { id, city }) = store.find_city(&args.city)? else {
+        bail!(
+            "'{}' is not in the city list, add it first to keep its forecasts",
+            args.city
+        );
+    };
+    if !args.stored {
+        let forecast = get_city_forecast(&city).await?;
+        store.save_snapshot(id, &forecast)?;
+    }
+
+    let [new, old] = &store.latest_snapshots(id, 2)?[..] else {
+        println!(
+            "Only one forecast of {} saved so far, run diff again later to see what changed",
+            city.name
+        );
+        return Ok(());
+    };
+    println!(
+        "{}: forecast of {} (version {}) vs {} (version {})\n",
+        city.name, new.fetched_at, new.version, old.fetched_at, old.version
+    );
+
+    let thresholds = Thresholds {
+        temperature: args.temp_threshold,
+        precipitation: args.rain_threshold,
+    };
+    let changes = diff::compare(&old.forecast, &new.forecast, thresholds);
+    if changes.is_empty() {
+        println!("   No change above the thresholds");
+    }
+    for change in changes {
+        println!("   {}", change.describe(opts));
+    }
+    Ok(())
+}
+
+/// What the weather actually was, as cards
+async fn handle_history(
+    store: Option<&Store>,
+    args: &HistoryArgs,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let to = args.to.unwrap_or(args.from);
+    if to < args.from {
+        bail!("--to ({to}) is before --from ({})", args.from);
+    }
+    if to >= Local::now().date_naive() {
+        bail!("--to ({to}) must be in the past, use get for the forecast");
+    }
+    let latest = latest_archive_day();
+    if to > latest {
+        bail!("--to ({to}) is too recent, the archive only goes up to {latest}");
+    }
+
+    let city = get_city(store, &args.city).await?;
+    let history = get_city_history(&city, args.from, to).await?;
+
+    let days: Vec<usize> = (0..history.len()).collect();
+    let opts = RenderOptions {
+        past: true,
+        ..*opts
+    };
+    history.print_days_for_city(&city, &days, &opts);
+    println!();
+    Ok(())
+}
+
+/// How close the saved forecasts were to the weather observed afterwards
+async fn handle_verify(store: &Store, args: &VerifyArgs, opts: &RenderOptions) -> Result<()> {
+    let cities = match &args.city {
+        Some(name) => match store.find_city(name)? {
+            Some(stored) => vec![stored],
+            None => bail!("'{name}' is not in the city list, only stored cities keep forecasts"),
+        },
+        None => store.cities()?,
+    };
+    if cities.is_empty() {
+        println!("No cities stored yet, add some and their forecasts will be kept");
+        return Ok(());
+    }
+
+    let latest = latest_archive_day();
+    let mut verified = vec![];
+    for StoredCity { id, city } in cities {
+        let snapshots = store.snapshots(id)?;
+        let range = verify::forecast_range(&snapshots).filter(|&(from, _)| from <= latest);
+        let Some((from, to)) = range else {
+            println!(
+                "{}: nothing to verify yet, the archive only goes up to {latest}\n",
+                city.name
+            );
+            continue;
+        };
+
+        let observed = get_city_history(&city, from, to.min(latest)).await?;
+        let scores = verify::verify(&snapshots, &observed, args.max_lead);
+        println!(
+            "{} ({from} to {})",
+            color::bold(&city.name, opts.color),
+            to.min(latest)
+        );
+        if scores.is_empty() {
+            println!("   No forecast up to D+{} for these days\n", args.max_lead);
+            continue;
+        }
+        verify::print_scores(&scores, opts);
+        println!();
+        verified.push(scores);
+    }
+
+    if verified.len() > 1 {
+        println!("{}", color::bold("All cities", opts.color));
+        verify::print_scores(&verify::combine(&verified), opts);
+        println!();
+    }
+    Ok(())
+}
+
+/// Air quality and pollen, day by day
+async fn handle_air(store: Option<&Store>, args: &AirArgs, opts: &RenderOptions) -> Result<()> {
+    let city = get_city(store, &args.city).await?;
+    let air = get_city_air_quality(&city, args.days).await?;
+
+    println!(
+        "\n{}\n",
+        color::bold(&format!("Air quality in {city}"), opts.color)
+    );
+    air::print_air(&air, opts);
+    println!();
+    Ok(())
+}
+
+/// Waves and sea temperature, day by day
+async fn handle_marine(
+    store: Option<&Store>,
+    args: &MarineArgs,
+    opts: &RenderOptions,
+) -> Result<()> {
+    let city = get_city(store, &args.city).await?;
+    let sea = get_city_marine(&city, args.days).await?;
+    if !marine::has_data(&sea) {
+        bail!("No marine forecast for {}, is it on the coast?", city.name);
+    }
+
+    println!(
+        "\n{}\n",
+        color::bold(&format!("Sea near {city}"), opts.color)
+    );
+    marine::print_marine(&sea, opts);
+    println!();
+    Ok(())
+}
+
+/// Day indices selected by the --tomorrow and --day-after flags (today if none).
+fn selected_days(tomorrow: bool, day_after: bool) -> Vec<usize> {
     let mut days = vec![];
-    if args.tomorrow {
+    if tomorrow {
         days.push(1);
     }
-    if args.day_after {
+    if day_after {
         days.push(2);
     }
-    if !args.tomorrow && !args.day_after {
+    if !tomorrow && !day_after {
         days.push(0);
     }
-    let city = get_city(&args.city).await?;
-    let forecast = get_city_forecast(&city).await?;
-
-    forecast.print_days_for_city(&city, &days);
-    println!();
-    Ok(())
+    days
 }
 
-pub async fn get_city(name: &str) -> Result<City> {
-    // Try from favourites
-    if let Some(city) = get_favourite_cities()
-        .into_iter()
-        .find(|c| c.name.eq_ignore_ascii_case(name))
+pub async fn get_city(store: Option<&Store>, name: &str) -> Result<City> {
+    // Try from the database
+    if let Some(store) = store
+        && let Some(StoredCity { city, .. }) = store.find_city(name)?
     {
-        println!("'{}' found inside our list of favourites!", city.name);
+        println!("'{}' found inside our list of cities!", city.name);
         return Ok(city);
     }
 
     // Try using the geocoding api
-    println!("'{}' not in favourites. Searching online...\n", name);
+    println!("'{}' not in our cities. Searching online...\n", name);
 
     let response = get_geocode(name).await?;
     let city = City::try_from(response)?;
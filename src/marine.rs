@@ -0,0 +1,95 @@
+use crate::display::{RenderOptions, label_for};
+use crate::models::{ForecastResponse, MarineDay, MarineResponse};
+use crate::table::print_rows;
+use crate::wind::compass_point;
+
+// Sea conditions for coastal cities, from the open-meteo marine api (wave models).
+// Waves are daily maxima, the sea temperature only comes by hour: it's averaged per day.
+// Away from the sea every value is null.
+
+/// Mean of the hourly sea temperatures of each date
+fn daily_sea_temperature(marine: &MarineResponse, date: &str) -> Option<f64> {
+    let hourly = &marine.hourly;
+    let values: Vec<f64> = hourly
+        .time
+        .iter()
+        .zip(&hourly.sea_surface_temperature)
+        .filter(|(time, _)| time.starts_with(date))
+        .filter_map(|(_, value)| *value)
+        .collect();
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Each day of the marine forecast with its date.
+pub fn daily(marine: &MarineResponse) -> Vec<(String, MarineDay)> {
+    let d = &marine.daily;
+    d.time
+        .iter()
+        .enumerate()
+        .map(|(id, date)| {
+            let day = MarineDay {
+                wave_height: d.wave_height_max[id],
+                wave_period: d.wave_period_max[id],
+                wave_direction: d.wave_direction_dominant[id],
+                swell_height: d.swell_wave_height_max[id],
+                sea_temperature: daily_sea_temperature(marine, date),
+            };
+            (date.clone(), day)
+        })
+        .collect()
+}
+
+/// Whether the wave models cover the place at all.
+pub fn has_data(marine: &MarineResponse) -> bool {
+    marine.daily.wave_height_max.iter().any(Option::is_some)
+}
+
+/// Set the sea conditions of each forecast day (empty past the marine forecast).
+pub fn annotate(forecast: &mut ForecastResponse, marine: &MarineResponse) {
+    let days = daily(marine);
+    let sea = forecast
+        .daily
+        .time
+        .iter()
+        .map(|date| {
+            days.iter()
+                .find(|(day, _)| day == date)
+                .map(|(_, sea)| *sea)
+                .unwrap_or_default()
+        })
+        .collect();
+    forecast.marine = Some(sea);
+}
+
+/// One row per day: waves, swell and sea temperature.
+pub fn print_marine(marine: &MarineResponse, opts: &RenderOptions) {
+    let days = daily(marine);
+    if days.is_empty() {
+        println!("No marine data available");
+        return;
+    }
+
+    let units = &marine.daily_units;
+    let sea_unit = &marine.hourly_units.sea_surface_temperature;
+    let missing = || "-".to_string();
+    let value = |v: Option<f64>, unit: &str| v.map_or_else(missing, |v| format!("{v:.1} {unit}"));
+    let rows: Vec<Vec<String>> = days
+        .iter()
+        .enumerate()
+        .map(|(id, (date, day))| {
+            vec![
+                format!("{} {date}", label_for(id, date, opts)),
+                value(day.wave_height, &units.wave_height_max),
+                day.wave_period
+                    .map_or_else(missing, |v| format!("{v:.0} {}", units.wave_period_max)),
+                day.wave_direction
+                    .map_or_else(missing, |v| format!("{:>2} {v:>3.0}°", compass_point(v))),
+                value(day.swell_height, &units.swell_wave_height_max),
+                value(day.sea_temperature, sea_unit),
+            ]
+        })
+        .collect();
+
+    let headers = ["Day", "Waves", "Period", "From", "Swell", "Sea"];
+    print_rows(&headers, &rows, &[0], opts);
+}
@@ -0,0 +1,241 @@
+use crate::db::Store;
+use crate::models::ForecastResponse;
+use crate::open_meteo::get_city_forecast;
+use anyhow::{Context, Result};
+use axum::Router;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpListener;
+
+// Prometheus exporter: the forecast of every stored city as gauges, refreshed on an interval,
+// and counters about our own calls to open-meteo (recorded by open_meteo::fetch_json).
+// Everything is written in the text exposition format by hand, it's only a few lines.
+
+/// Upper bounds (seconds) of the upstream latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+#[derive(Debug, Default)]
+struct UpstreamStats {
+    requests: u64,
+    errors: u64,
+    /// Count per bucket of `LATENCY_BUCKETS` (not cumulative), the last one is +Inf.
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    latency_sum: f64,
+}
+
+/// Stats per upstream api name, for the whole process.
+static UPSTREAM: Mutex<BTreeMap<String, UpstreamStats>> = Mutex::new(BTreeMap::new());
+
+/// Count one request to `api`, how long it took and whether it failed.
+pub fn record_upstream(api: &str, elapsed: Duration, ok: bool) {
+    let mut upstream = UPSTREAM.lock().unwrap_or_else(|e| e.into_inner());
+    let stats = upstream.entry(api.to_string()).or_default();
+    let seconds = elapsed.as_secs_f64();
+
+    stats.requests += 1;
+    if !ok {
+        stats.errors += 1;
+    }
+    let bucket = LATENCY_BUCKETS
+        .iter()
+        .position(|&le| seconds <= le)
+        .unwrap_or(LATENCY_BUCKETS.len());
+    stats.buckets[bucket] += 1;
+    stats.latency_sum += seconds;
+}
+
+/// Last forecast of each city, `None` when its last refresh failed.
+#[derive(Default)]
+pub struct Snapshot {
+    cities: Vec<(String, Option<ForecastResponse>)>,
+    refreshed_at: Option<SystemTime>,
+}
+
+/// Fetch the forecast of every stored city.
+async fn refresh(store: &Store) -> Result<Snapshot> {
+    let mut cities = vec![];
+    for stored in store.cities()? {
+        let forecast = match get_city_forecast(&stored.city).await {
+            Ok(forecast) => Some(forecast),
+            Err(e) => {
+                eprintln!("{e:#}");
+                None
+            }
+        };
+        cities.push((stored.city.name, forecast));
+    }
+    Ok(Snapshot {
+        cities,
+        refreshed_at: Some(SystemTime::now()),
+    })
+}
+
+/// Serve `/metrics` on `addr` and refresh the forecasts every `interval` until stopped.
+pub async fn run_exporter(store: Store, addr: SocketAddr, interval: Duration) -> Result<()> {
+    let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Couldn't listen on {addr}"))?;
+    println!("Exporting metrics on http://{addr}/metrics");
+
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(snapshot.clone());
+    let mut server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => match refresh(&store).await {
+                Ok(fresh) => *snapshot.lock().unwrap_or_else(|e| e.into_inner()) = fresh,
+                // keep exporting the previous values
+                Err(e) => eprintln!("Refresh failed: {e:#}"),
+            },
+            result = &mut server => {
+                return result
+                    .context("Metrics server crashed")?
+                    .context("HTTP server error");
+            }
+        }
+    }
+}
+
+async fn metrics(State(snapshot): State<Arc<Mutex<Snapshot>>>) -> impl IntoResponse {
+    let snapshot = snapshot.lock().unwrap_or_else(|e| e.into_inner());
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render(&snapshot),
+    )
+}
+
+/// Escape a label value (backslash, double quote and newline).
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Metrics in the Prometheus text format.
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    // writing to a String can't fail
+    let mut line = |text: String| {
+        let _ = writeln!(out, "{text}");
+    };
+
+    line("# HELP weather_temperature_celsius Forecast temperature, day 0 is today.".into());
+    line("# TYPE weather_temperature_celsius gauge".into());
+    for (city, forecast) in &snapshot.cities {
+        let Some(forecast) = forecast else { continue };
+        let daily = &forecast.daily;
+        let kinds = [
+            ("min", &daily.temperature_2m_min),
+            ("max", &daily.temperature_2m_max),
+            ("mean", &daily.temperature_2m_mean),
+        ];
+        for (kind, values) in kinds {
+            for (day, value) in values.iter().enumerate() {
+                line(format!(
+                    "weather_temperature_celsius{{city=\"{}\",kind=\"{kind}\",day=\"{day}\"}} {value}",
+                    label(city)
+                ));
+            }
+        }
+    }
+
+    line("# HELP weather_precipitation_mm Forecast precipitation sum of the day.".into());
+    line("# TYPE weather_precipitation_mm gauge".into());
+    for (city, forecast) in &snapshot.cities {
+        let Some(forecast) = forecast else { continue };
+        for (day, value) in forecast.daily.precipitation_sum.iter().enumerate() {
+            line(format!(
+                "weather_precipitation_mm{{city=\"{}\",day=\"{day}\"}} {value}",
+                label(city)
+            ));
+        }
+    }
+
+    line("# HELP weather_code WMO weather code of today's forecast.".into());
+    line("# TYPE weather_code gauge".into());
+    for (city, forecast) in &snapshot.cities {
+        let Some(code) = forecast.as_ref().and_then(|f| f.daily.weather_code.first()) else {
+            continue;
+        };
+        line(format!("weather_code{{city=\"{}\"}} {code}", label(city)));
+    }
+
+    line("# HELP weather_forecast_up Whether the last refresh of the city succeeded.".into());
+    line("# TYPE weather_forecast_up gauge".into());
+    for (city, forecast) in &snapshot.cities {
+        let up = u8::from(forecast.is_some());
+        line(format!(
+            "weather_forecast_up{{city=\"{}\"}} {up}",
+            label(city)
+        ));
+    }
+
+    if let Some(refreshed_at) = snapshot.refreshed_at {
+        let seconds = refreshed_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        line("# HELP weather_last_refresh_timestamp_seconds End of the last refresh.".into());
+        line("# TYPE weather_last_refresh_timestamp_seconds gauge".into());
+        line(format!("weather_last_refresh_timestamp_seconds {seconds}"));
+    }
+
+    let upstream = UPSTREAM.lock().unwrap_or_else(|e| e.into_inner());
+    line("# HELP weather_upstream_requests_total Requests sent to open-meteo.".into());
+    line("# TYPE weather_upstream_requests_total counter".into());
+    for (api, stats) in upstream.iter() {
+        line(format!(
+            "weather_upstream_requests_total{{api=\"{}\"}} {}",
+            label(api),
+            stats.requests
+        ));
+    }
+
+    line("# HELP weather_upstream_errors_total Failed requests to open-meteo.".into());
+    line("# TYPE weather_upstream_errors_total counter".into());
+    for (api, stats) in upstream.iter() {
+        line(format!(
+            "weather_upstream_errors_total{{api=\"{}\"}} {}",
+            label(api),
+            stats.errors
+        ));
+    }
+
+    line("# HELP weather_upstream_request_duration_seconds Latency of open-meteo requests.".into());
+    line("# TYPE weather_upstream_request_duration_seconds histogram".into());
+    for (api, stats) in upstream.iter() {
+        let api = label(api);
+        let mut cumulative = 0;
+        for (i, count) in stats.buckets.iter().enumerate() {
+            cumulative += count;
+            let le = LATENCY_BUCKETS
+                .get(i)
+                .map_or("+Inf".to_string(), |le| le.to_string());
+            line(format!(
+                "weather_upstream_request_duration_seconds_bucket{{api=\"{api}\",le=\"{le}\"}} {cumulative}"
+            ));
+        }
+        line(format!(
+            "weather_upstream_request_duration_seconds_sum{{api=\"{api}\"}} {}",
+            stats.latency_sum
+        ));
+        line(format!(
+            "weather_upstream_request_duration_seconds_count{{api=\"{api}\"}} {}",
+            stats.requests
+        ));
+    }
+
+    out
+}
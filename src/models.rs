@@ -1,14 +1,40 @@
-use crate::wmo::{describe, emoji};
+use crate::air::AqiLevel;
+use crate::color;
+use crate::display::{
+    self, RenderOptions, day_label, label_for, pad_right, side_by_side, truncate, wrap,
+};
+use crate::sun;
+use crate::wind;
+use crate::wmo::{Category, describe, icon, is_severe};
 use anyhow::{Error, bail};
-use serde::Deserialize;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
+use utoipa::ToSchema;
+
+/// Preferred width of a card (and of the city header box).
+const CARD_WIDTH: usize = 45;
+/// Narrowest a card can get before we give up on shrinking.
+const MIN_CARD_WIDTH: usize = 24;
+/// Width a card needs to be put next to another one.
+const SIDE_CARD_WIDTH: usize = 32;
+const CARD_INDENT: &str = "   ";
 
 /// City with coordinates
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct City {
+    #[schema(example = "Brussels")]
     pub name: String,
+    /// Latitude, -90 to 90
+    #[schema(example = 50.8505)]
     pub lat: f64,
+    /// Longitude, -180 to 180
+    #[schema(example = 4.3488)]
     pub long: f64,
+    /// On the sea: marine forecast shown with the weather
+    #[serde(default)]
+    pub coastal: bool,
 }
 
 impl fmt::Display for City {
@@ -18,30 +44,235 @@ impl fmt::Display for City {
 }
 
 // Structs to deserialize open-meteo forecast api results based of this kind of response:
-// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&daily=weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean&timezone=auto
+// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&daily=weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean,precipitation_sum,precipitation_hours,precipitation_probability_max,sunrise,sunset,daylight_duration,sunshine_duration,wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant&timezone=auto
 
-#[derive(Debug, Deserialize)]
+/// Daily forecast as returned by open-meteo, one entry per day in each `daily` array
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct ForecastResponse {
+    /// Timezone of the location ("Europe/Brussels"), the times of `daily` are local to it
+    #[serde(default)]
+    pub timezone: String,
     pub daily: DailyForecast,
     pub daily_units: DailyUnits,
+    /// Climate normals of each day, added to the cards of stored cities (not from open-meteo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normals: Option<Vec<DayNormal>>,
+    /// Highest European AQI of each day, when asked for (from the air quality api)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub european_aqi: Option<Vec<Option<f64>>>,
+    /// Sea conditions of each day, for coastal cities (from the marine api)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marine: Option<Vec<MarineDay>>,
+    /// Spread of the ensemble members for each day, when asked for (from the ensemble api)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ensemble: Option<Vec<Option<EnsembleDay>>>,
+    /// Icon and severity of each day's weather code, added by the API (see `wmo::annotate`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weather: Option<Vec<DayWeather>>,
+}
+
+/// 10th, 50th (median) and 90th percentiles of the ensemble members
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct Percentiles {
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
+/// What the members of an ensemble forecast say about one day
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct EnsembleDay {
+    /// Number of members with a value for the day
+    pub members: usize,
+    /// °C
+    pub temperature_min: Percentiles,
+    /// °C
+    pub temperature_max: Percentiles,
+    /// mm
+    pub precipitation: Percentiles,
+    /// Share of the members with some rain (at least 0.1 mm), 0 to 100
+    pub rain_chance: f64,
+}
+
+/// Sea conditions of a day, `None` when the wave models don't cover it
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+pub struct MarineDay {
+    /// Highest significant wave height (m)
+    pub wave_height: Option<f64>,
+    /// Longest wave period (s)
+    pub wave_period: Option<f64>,
+    /// Where the waves come from (degrees, 0 is north)
+    pub wave_direction: Option<f64>,
+    /// Highest swell wave height (m)
+    pub swell_height: Option<f64>,
+    /// Mean sea surface temperature (°C)
+    pub sea_temperature: Option<f64>,
+}
+
+/// Usual temperatures (°C) of a calendar day, averaged over several years
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+pub struct DayNormal {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+/// A weather code as the CLI shows it, so that clients don't need their own table of codes
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DayWeather {
+    #[schema(example = "🌧️")]
+    pub icon: String,
+    pub category: Category,
+    /// WMO 4677 description of the code
+    pub description: String,
+    /// Thunderstorms and freezing drizzle or rain
+    pub severe: bool,
 }
 
-#[derive(Debug, Deserialize)]
+/// Unit of each `daily` field (°C, mm, ...)
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct DailyUnits {
     pub time: String,
     pub temperature_2m_max: String,
     pub temperature_2m_min: String,
     pub weather_code: String,
     pub temperature_2m_mean: String,
+    pub precipitation_sum: String,
+    #[serde(default)]
+    pub precipitation_hours: String,
+    #[serde(default)]
+    pub precipitation_probability_max: String,
+    #[serde(default)]
+    pub sunrise: String,
+    #[serde(default)]
+    pub sunset: String,
+    #[serde(default)]
+    pub daylight_duration: String,
+    #[serde(default)]
+    pub sunshine_duration: String,
+    #[serde(default)]
+    pub wind_speed_10m_max: String,
+    #[serde(default)]
+    pub wind_gusts_10m_max: String,
+    #[serde(default)]
+    pub wind_direction_10m_dominant: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
 pub struct DailyForecast {
+    /// Dates (YYYY-MM-DD) in the local timezone of the location
     pub time: Vec<String>,
     pub temperature_2m_max: Vec<f64>,
     pub temperature_2m_min: Vec<f64>,
+    /// WMO weather codes
     pub weather_code: Vec<u32>,
     pub temperature_2m_mean: Vec<f64>,
+    pub precipitation_sum: Vec<f64>,
+    /// Hours with some precipitation (empty in snapshots saved before it was fetched, null for
+    /// the models which don't give it)
+    #[serde(default)]
+    pub precipitation_hours: Vec<Option<f64>>,
+    /// Highest hourly probability of precipitation (%), null in the archive and for some models
+    #[serde(default)]
+    pub precipitation_probability_max: Vec<Option<u8>>,
+    /// Local times (YYYY-MM-DDTHH:MM), see the `sun` module to parse them
+    #[serde(default)]
+    pub sunrise: Vec<String>,
+    #[serde(default)]
+    pub sunset: Vec<String>,
+    /// Seconds between sunrise and sunset
+    #[serde(default)]
+    pub daylight_duration: Vec<f64>,
+    /// Seconds of direct sunshine, null for some models
+    #[serde(default)]
+    pub sunshine_duration: Vec<Option<f64>>,
+    /// Wind values are null for the models which don't give them
+    #[serde(default)]
+    pub wind_speed_10m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    pub wind_gusts_10m_max: Vec<Option<f64>>,
+    /// Where the wind mostly comes from (degrees, 0 is north)
+    #[serde(default)]
+    pub wind_direction_10m_dominant: Vec<Option<f64>>,
+}
+
+/// Forecasts for the tests, one day after the other, in °C and mm unless told otherwise.
+#[cfg(test)]
+pub struct ForecastBuilder {
+    forecast: ForecastResponse,
+}
+
+#[cfg(test)]
+impl Default for ForecastBuilder {
+    fn default() -> Self {
+        let units = DailyUnits {
+            time: "iso8601".to_string(),
+            temperature_2m_max: "°C".to_string(),
+            temperature_2m_min: "°C".to_string(),
+            weather_code: "wmo code".to_string(),
+            temperature_2m_mean: "°C".to_string(),
+            precipitation_sum: "mm".to_string(),
+            ..Default::default()
+        };
+        ForecastBuilder {
+            forecast: ForecastResponse {
+                timezone: "GMT".to_string(),
+                daily_units: units,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+impl ForecastBuilder {
+    /// Add a dry day, its mean halfway between `min` and `max`.
+    pub fn day(mut self, date: &str, code: u32, min: f64, max: f64) -> Self {
+        let daily = &mut self.forecast.daily;
+        daily.time.push(date.to_string());
+        daily.weather_code.push(code);
+        daily.temperature_2m_min.push(min);
+        daily.temperature_2m_max.push(max);
+        daily.temperature_2m_mean.push((min + max) / 2.0);
+        daily.precipitation_sum.push(0.0);
+        self
+    }
+
+    /// Units of the temperatures and of the precipitation.
+    pub fn units(mut self, temperature: &str, precipitation: &str) -> Self {
+        let units = &mut self.forecast.daily_units;
+        units.temperature_2m_min = temperature.to_string();
+        units.temperature_2m_max = temperature.to_string();
+        units.temperature_2m_mean = temperature.to_string();
+        units.precipitation_sum = precipitation.to_string();
+        self
+    }
+
+    /// Precipitation of the last day added.
+    pub fn rain(mut self, mm: f64) -> Self {
+        if let Some(sum) = self.forecast.daily.precipitation_sum.last_mut() {
+            *sum = mm;
+        }
+        self
+    }
+
+    /// Sunrise and sunset (local times) and daylight (seconds) of the last day added.
+    pub fn sun(mut self, sunrise: &str, sunset: &str, daylight: f64) -> Self {
+        let daily = &mut self.forecast.daily;
+        daily.sunrise.push(sunrise.to_string());
+        daily.sunset.push(sunset.to_string());
+        daily.daylight_duration.push(daylight);
+        self
+    }
+
+    pub fn timezone(mut self, timezone: &str) -> Self {
+        self.forecast.timezone = timezone.to_string();
+        self
+    }
+
+    pub fn build(self) -> ForecastResponse {
+        self.forecast
+    }
 }
 
 impl ForecastResponse {
@@ -49,71 +280,599 @@ impl ForecastResponse {
         self.daily.time.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.daily.time.is_empty()
+    }
+
+    /// Lines of the card for one day, without the separator line.
+    /// `width` is the width available for the text (indent excluded).
+    pub fn card_lines(&self, id: usize, width: usize, opts: &RenderOptions) -> Vec<String> {
+        if id >= self.len() {
+            return vec![format!("{CARD_INDENT}[{}] No data", day_label(id))];
+        }
+
+        let date = &self.daily.time[id];
+        // Label (Today, D+1, D+2 etc, or the weekday for past weather)
+        let label = label_for(id, date, opts);
+        let code = self.daily.weather_code[id];
+        let icon = icon(code, opts.unicode);
+        let desc = describe(code);
+
+        let t_min = self.daily.temperature_2m_min[id];
+        let t_max = self.daily.temperature_2m_max[id];
+        let t_mean = self.daily.temperature_2m_mean[id];
+
+        let u = &self.daily_units.temperature_2m_min; // all temps use celsius 
+        let thermometer = if opts.unicode { "🌡️ " } else { "" };
+
+        // Card
+        let mut lines = vec![format!("[{label}] {date}")];
+        // the description is the only long text: wrap it under the icon
+        let icon_prefix = format!("{icon}  ");
+        let desc_width = width.saturating_sub(display::width(&icon_prefix)).max(1);
+        for (i, part) in wrap(desc, desc_width).into_iter().enumerate() {
+            let prefix = if i == 0 {
+                icon_prefix.clone()
+            } else {
+                " ".repeat(display::width(&icon_prefix))
+            };
+            let part = if is_severe(code) {
+                color::severe(&part, opts.color)
+            } else {
+                part
+            };
+            lines.push(format!("{prefix}{part}"));
+        }
+        let temp = |t: f64| color::temperature(&format!("{t:>5.1} {u}"), t, opts.color);
+        lines.push(format!("{thermometer}Min:  {}", temp(t_min)));
+        lines.push(format!("{thermometer}Max:  {}", temp(t_max)));
+        lines.push(format!("{thermometer}Mean: {}", temp(t_mean)));
+        if let Some(line) = self.rain_line(id, opts) {
+            lines.push(line);
+        }
+        lines.extend(self.wind_lines(id, width, opts));
+        if let Some(line) = self.sun_line(id, opts) {
+            lines.push(line);
+        }
+        if let Some(normal) = self.normals.as_ref().and_then(|n| n.get(id)) {
+            lines.push(compared_to_normal(t_max - normal.max, date, u));
+        }
+        if let Some(aqi) = self
+            .european_aqi
+            .as_ref()
+            .and_then(|a| a.get(id).copied().flatten())
+        {
+            lines.push(aqi_line(aqi, opts));
+        }
+        if let Some(line) = self
+            .marine
+            .as_ref()
+            .and_then(|days| marine_line(days.get(id)?, opts))
+        {
+            lines.push(line);
+        }
+        if let Some(day) = self.ensemble.as_ref().and_then(|days| *days.get(id)?) {
+            lines.push(ensemble_line(&day, u, opts));
+        }
+
+        lines
+            .into_iter()
+            .map(|l| format!("{CARD_INDENT}{}", truncate(&l, width)))
+            .collect()
+    }
+
+    /// "☔ Rain:  4.2 mm in 3 h (80%)", or "Rain: none (10%)" for a dry day. `None` for
+    /// snapshots saved without the precipitation hours.
+    fn rain_line(&self, id: usize, opts: &RenderOptions) -> Option<String> {
+        let daily = &self.daily;
+        let hours = daily.precipitation_hours.get(id)?;
+        let sum = daily.precipitation_sum[id];
+        let amount = if sum > 0.0 {
+            let unit = &self.daily_units.precipitation_sum;
+            let hours = hours.map_or("-".to_string(), |hours| format!("{hours:.0}"));
+            format!("{sum:>5.1} {unit} in {hours} h")
+        } else {
+            "none".to_string()
+        };
+        let probability = daily
+            .precipitation_probability_max
+            .get(id)
+            .copied()
+            .flatten()
+            .map_or(String::new(), |p| format!(" ({p}%)"));
+        let umbrella = if opts.unicode { "☔ " } else { "" };
+        Some(format!("{umbrella}Rain: {amount}{probability}"))
+    }
+
+    /// "🌬️ Wind: ↙ NE 25 km/h, gusts 48, moderate breeze", wrapped to `width` and coloured from
+    /// a gale on. Empty for snapshots saved without the wind.
+    fn wind_lines(&self, id: usize, width: usize, opts: &RenderOptions) -> Vec<String> {
+        let daily = &self.daily;
+        let (Some(&speed), Some(&gusts), Some(&direction)) = (
+            daily.wind_speed_10m_max.get(id),
+            daily.wind_gusts_10m_max.get(id),
+            daily.wind_direction_10m_dominant.get(id),
+        ) else {
+            return vec![];
+        };
+        let unit = &self.daily_units.wind_speed_10m_max;
+        // models without the wind give nulls
+        let force = speed.map(wind::beaufort);
+        let wind = match (speed, direction) {
+            (Some(speed), Some(direction)) => {
+                wind::format_wind(speed, direction, unit, opts.unicode)
+            }
+            (Some(speed), None) => format!("{speed:.0} {unit}"),
+            (None, _) => "-".to_string(),
+        };
+        let gusts = gusts.map_or("-".to_string(), |gusts| format!("{gusts:.0}"));
+        let text = match force {
+            Some(force) => format!(
+                "Wind: {wind}, gusts {gusts}, {}",
+                wind::beaufort_description(force)
+            ),
+            None => format!("Wind: {wind}, gusts {gusts}"),
+        };
+
+        let icon = if opts.unicode { "🌬️ " } else { "" };
+        let icon_width = display::width(icon);
+        wrap(&text, width.saturating_sub(icon_width).max(1))
+            .into_iter()
+            .enumerate()
+            .map(|(i, part)| {
+                let prefix = if i == 0 {
+                    icon.to_string()
+                } else {
+                    " ".repeat(icon_width)
+                };
+                let part = if force.is_some_and(|force| force >= wind::SEVERE_BEAUFORT) {
+                    color::severe(&part, opts.color)
+                } else {
+                    part
+                };
+                format!("{prefix}{part}")
+            })
+            .collect()
+    }
+
+    /// "🌅 Sun: 08:12–18:41, 4.5 h of sun", `None` without sun data
+    fn sun_line(&self, id: usize, opts: &RenderOptions) -> Option<String> {
+        let tz = sun::timezone(self).ok()?;
+        let day = sun::day(self, tz, id).ok()??;
+        let (icon, dash) = if opts.unicode {
+            ("🌅 ", "–")
+        } else {
+            ("", "-")
+        };
+        let sunshine = day.sunshine.map_or(String::new(), |sunshine| {
+            let hours = sunshine.num_minutes() as f64 / 60.0;
+            format!(", {hours:.1} h of sun")
+        });
+        Some(format!(
+            "{icon}Sun: {}{dash}{}{sunshine}",
+            day.sunrise.format("%H:%M"),
+            day.sunset.format("%H:%M")
+        ))
+    }
+
     // Formatting code to build cards (labels & emoji idea from gpt)
-    pub fn print_days_for_city(&self, city: &City, indices: &[usize]) {
-        if self.len() == 0 || indices.is_empty() {
+    pub fn print_days_for_city(&self, city: &City, indices: &[usize], opts: &RenderOptions) {
+        if self.is_empty() || indices.is_empty() {
             println!("No forecast data available for {}", city.name);
             return;
         }
+        let b = opts.box_chars();
 
-        // City header
-        let header = city.to_string();
-        let width = usize::max(45, header.len() + 4);
-        let thick_line = "═".repeat(width);
-        let line = "─".repeat(width);
+        // City header, shrunk if the terminal is too narrow (2 columns for the box sides)
+        let max_width = opts.width.saturating_sub(2).max(MIN_CARD_WIDTH);
+        let header = truncate(&city.to_string(), max_width - 2);
+        let width = (display::width(&header) + 4).max(CARD_WIDTH).min(max_width);
+        let header = color::bold(&header, opts.color);
+        let thick_line = b.thick.repeat(width);
         // just the spaces between the city print and right side of the box
-        let spaces = " ".repeat(width - header.chars().count() - 1);
+        let header = pad_right(&header, width - 1);
 
-        println!("\n╔{thick_line}╗");
-        println!("║ {header}{spaces}║");
-        println!("╚{thick_line}╝");
+        println!("\n{}{thick_line}{}", b.top_left, b.top_right);
+        println!("{} {header}{}", b.side, b.side);
+        println!("{}{thick_line}{}", b.bottom_left, b.bottom_right);
+
+        // Cards side by side if at least two of them fit, else one under the other
+        let separator = format!(" {} ", b.column);
+        let sep_width = display::width(&separator);
+        let columns =
+            ((opts.width + sep_width) / (SIDE_CARD_WIDTH + sep_width)).clamp(1, indices.len());
+        let card_width = if columns > 1 {
+            ((opts.width - sep_width * (columns - 1)) / columns).min(CARD_WIDTH)
+        } else {
+            width
+        };
+        let text_width = card_width.saturating_sub(display::width(CARD_INDENT));
 
-        for &id in indices {
-            if id >= self.len() {
-                println!("  [D+{id}] No data");
-                continue;
+        for row in indices.chunks(columns) {
+            let cards: Vec<Vec<String>> = row
+                .iter()
+                .map(|&id| self.card_lines(id, text_width, opts))
+                .collect();
+            for line in side_by_side(&cards, card_width, &separator) {
+                println!("{line}");
             }
 
-            let date = &self.daily.time[id];
-            let code = self.daily.weather_code[id];
-            let icon = emoji(code);
-            let desc = describe(code);
+            // long line after the end of a day
+            let line_width = card_width * row.len() + sep_width * (row.len() - 1);
+            println!("{}", b.line.repeat(line_width));
+        }
+    }
+}
+
+/// "Max +4.2 °C above normal for 17 Oct"
+fn compared_to_normal(difference: f64, date: &str, unit: &str) -> String {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_or(date.to_string(), |d| d.format("%-d %b").to_string());
+    if difference.abs() < 0.5 {
+        return format!("Max close to normal for {day}");
+    }
+    let side = if difference > 0.0 { "above" } else { "below" };
+    format!("Max {difference:+.1} {unit} {side} normal for {day}")
+}
 
-            let t_min = self.daily.temperature_2m_min[id];
-            let t_max = self.daily.temperature_2m_max[id];
-            let t_mean = self.daily.temperature_2m_mean[id];
+/// "💨 Air: 35 EAQI (fair)", coloured from poor on
+fn aqi_line(aqi: f64, opts: &RenderOptions) -> String {
+    let level = AqiLevel::from_index(aqi);
+    let text = format!("Air:  {aqi:>5.0} EAQI ({level})");
+    let text = if level >= AqiLevel::Poor {
+        color::severe(&text, opts.color)
+    } else {
+        text
+    };
+    let wind = if opts.unicode { "💨 " } else { "" };
+    format!("{wind}{text}")
+}
 
-            let u = &self.daily_units.temperature_2m_min; // all temps use celsius 
+/// "🌊 Waves 1.2 m (6 s), sea 14.8 °C", `None` without wave nor sea data
+fn marine_line(day: &MarineDay, opts: &RenderOptions) -> Option<String> {
+    let mut parts = vec![];
+    if let Some(height) = day.wave_height {
+        let period = day
+            .wave_period
+            .map_or(String::new(), |period| format!(" ({period:.0} s)"));
+        parts.push(format!("Waves {height:.1} m{period}"));
+    }
+    if let Some(sea) = day.sea_temperature {
+        parts.push(format!("sea {sea:.1} °C"));
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    let wave = if opts.unicode { "🌊 " } else { "" };
+    Some(format!("{wave}{}", parts.join(", ")))
+}
 
-            // Label (Today, D+1, D+2 etc)
-            let label = match id {
-                0 => "Today".to_string(),
-                1 => "Tomorrow".to_string(),
-                n => format!("D+{n}"),
-            };
+/// "↕ Max 15–19 °C, 70% chance of rain": where 80% of the ensemble members are
+fn ensemble_line(day: &EnsembleDay, unit: &str, opts: &RenderOptions) -> String {
+    let (arrow, dash) = if opts.unicode {
+        ("↕ ", "–")
+    } else {
+        ("", "-")
+    };
+    let max = day.temperature_max;
+    format!(
+        "{arrow}Max {:.0}{dash}{:.0} {unit}, {:.0}% chance of rain",
+        max.p10, max.p90, day.rain_chance
+    )
+}
 
-            // Card
-            println!("   [{label}] {date}");
-            println!("   {icon}  {desc}");
-            println!("   🌡️ Min:   {t_min:.1} {u}");
-            println!("   🌡️ Max:  {t_max:.1} {u}");
-            println!("   🌡️ Mean: {t_mean:.1} {u}");
+// Structs to deserialize open-meteo forecast api results for given models:
+// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&daily=weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean,precipitation_sum,precipitation_hours,precipitation_probability_max,sunrise,sunset,daylight_duration,sunshine_duration,wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant&models=icon_d2,ecmwf_ifs025&timezone=auto
 
-            // long line after the end of a day
-            println!("{}", line);
+/// Daily forecast of one or several weather models. With several, every field is suffixed
+/// with the model ("temperature_2m_max_icon_d2"), and short range models are null after
+/// their last day.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelsResponse {
+    #[serde(default)]
+    pub timezone: String,
+    pub daily: ModelsDaily,
+    pub daily_units: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelsDaily {
+    pub time: Vec<String>,
+    #[serde(flatten)]
+    pub fields: BTreeMap<String, ModelValues>,
+}
+
+/// Values of one daily variable: numbers, or local times for sunrise and sunset
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ModelValues {
+    Numbers(Vec<Option<f64>>),
+    Times(Vec<Option<String>>),
+}
+
+impl ModelsResponse {
+    /// The usual forecast of one model (its suffixed fields, or the plain ones when it was the
+    /// only model asked), up to the last day it has.
+    pub fn forecast_of(&self, model: &str) -> Result<ForecastResponse, Error> {
+        let values = |name: &str| -> Option<&ModelValues> {
+            let fields = &self.daily.fields;
+            fields
+                .get(&format!("{name}_{model}"))
+                .or_else(|| fields.get(name))
+        };
+        let field = |name: &str| -> Vec<Option<f64>> {
+            match values(name) {
+                Some(ModelValues::Numbers(numbers)) => numbers.clone(),
+                _ => vec![],
+            }
+        };
+
+        let unit = |name: &str| -> String {
+            let units = &self.daily_units;
+            units
+                .get(&format!("{name}_{model}"))
+                .or_else(|| units.get(name))
+                .cloned()
+                .unwrap_or_default()
+        };
+
+        let (max, min, code) = (
+            field("temperature_2m_max"),
+            field("temperature_2m_min"),
+            field("weather_code"),
+        );
+        let (mean, precipitation) = (field("temperature_2m_mean"), field("precipitation_sum"));
+        // days until the first missing value
+        let days = (0..self.daily.time.len())
+            .take_while(|&id| {
+                [&max, &min, &code, &mean, &precipitation]
+                    .iter()
+                    .all(|values| values.get(id).copied().flatten().is_some())
+            })
+            .count();
+        if days == 0 {
+            bail!("{model} has no forecast for this place");
         }
+        let known = |values: Vec<Option<f64>>| values.into_iter().take(days).flatten().collect();
+        // the variables some models don't have stay null
+        let optional = |name: &str| field(name).into_iter().take(days).collect();
+        // the sun is shown for all the days or none
+        let all_known = |values: Vec<Option<f64>>| {
+            let values: Option<Vec<f64>> = values.into_iter().take(days).collect();
+            values
+                .filter(|values| values.len() == days)
+                .unwrap_or_default()
+        };
+        let times = |name: &str| -> Vec<String> {
+            let Some(ModelValues::Times(times)) = values(name) else {
+                return vec![];
+            };
+            let times: Option<Vec<String>> = times.iter().take(days).cloned().collect();
+            times
+                .filter(|times| times.len() == days)
+                .unwrap_or_default()
+        };
+
+        Ok(ForecastResponse {
+            timezone: self.timezone.clone(),
+            daily: DailyForecast {
+                time: self.daily.time[..days].to_vec(),
+                temperature_2m_max: known(max),
+                temperature_2m_min: known(min),
+                weather_code: code
+                    .into_iter()
+                    .take(days)
+                    .flatten()
+                    .map(|c| c as u32)
+                    .collect(),
+                temperature_2m_mean: known(mean),
+                precipitation_sum: known(precipitation),
+                precipitation_hours: optional("precipitation_hours"),
+                precipitation_probability_max: field("precipitation_probability_max")
+                    .into_iter()
+                    .take(days)
+                    .map(|p| p.map(|p| p as u8))
+                    .collect(),
+                sunrise: times("sunrise"),
+                sunset: times("sunset"),
+                daylight_duration: all_known(field("daylight_duration")),
+                sunshine_duration: optional("sunshine_duration"),
+                wind_speed_10m_max: optional("wind_speed_10m_max"),
+                wind_gusts_10m_max: optional("wind_gusts_10m_max"),
+                wind_direction_10m_dominant: optional("wind_direction_10m_dominant"),
+            },
+            daily_units: DailyUnits {
+                time: unit("time"),
+                temperature_2m_max: unit("temperature_2m_max"),
+                temperature_2m_min: unit("temperature_2m_min"),
+                weather_code: unit("weather_code"),
+                temperature_2m_mean: unit("temperature_2m_mean"),
+                precipitation_sum: unit("precipitation_sum"),
+                precipitation_hours: unit("precipitation_hours"),
+                precipitation_probability_max: unit("precipitation_probability_max"),
+                wind_speed_10m_max: unit("wind_speed_10m_max"),
+                wind_gusts_10m_max: unit("wind_gusts_10m_max"),
+                wind_direction_10m_dominant: unit("wind_direction_10m_dominant"),
+                sunrise: unit("sunrise"),
+                sunset: unit("sunset"),
+                daylight_duration: unit("daylight_duration"),
+                sunshine_duration: unit("sunshine_duration"),
+            },
+            normals: None,
+            european_aqi: None,
+            marine: None,
+            ensemble: None,
+            weather: None,
+        })
     }
 }
 
+// Structs to deserialize open-meteo hourly forecast results based of this kind of response:
+// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&hourly=temperature_2m,precipitation_probability,precipitation,weather_code,wind_speed_10m,wind_direction_10m,wind_gusts_10m&forecast_hours=24&timezone=auto
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HourlyResponse {
+    pub hourly: HourlyForecast,
+    pub hourly_units: HourlyUnits,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HourlyUnits {
+    pub time: String,
+    pub temperature_2m: String,
+    pub precipitation_probability: String,
+    pub precipitation: String,
+    pub weather_code: String,
+    pub wind_speed_10m: String,
+    pub wind_direction_10m: String,
+    pub wind_gusts_10m: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HourlyForecast {
+    pub time: Vec<String>,
+    pub temperature_2m: Vec<f64>,
+    // some weather models don't give probabilities: null values
+    pub precipitation_probability: Vec<Option<u8>>,
+    pub precipitation: Vec<f64>,
+    pub weather_code: Vec<u32>,
+    pub wind_speed_10m: Vec<f64>,
+    /// Where the wind comes from (degrees, 0 is north)
+    pub wind_direction_10m: Vec<f64>,
+    pub wind_gusts_10m: Vec<f64>,
+}
+
+impl HourlyResponse {
+    pub fn len(&self) -> usize {
+        self.hourly.time.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hourly.time.is_empty()
+    }
+}
+
+// Structs to deserialize open-meteo air quality api results based of this kind of response:
+// https://air-quality-api.open-meteo.com/v1/air-quality?latitude=50.8505&longitude=4.3488&hourly=pm2_5,pm10,ozone,nitrogen_dioxide,european_aqi,birch_pollen&forecast_days=5&timezone=auto
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AirQualityResponse {
+    pub hourly: AirQualityHourly,
+    pub hourly_units: AirQualityUnits,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AirQualityUnits {
+    pub time: String,
+    pub pm2_5: String,
+    pub pm10: String,
+    pub ozone: String,
+    pub nitrogen_dioxide: String,
+    pub european_aqi: String,
+    /// grains/m³, the same for every pollen type
+    pub birch_pollen: String,
+}
+
+/// Hourly values, null when the model doesn't cover the hour (pollen is only forecast for
+/// Europe, during the season, for fewer days).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AirQualityHourly {
+    pub time: Vec<String>,
+    pub pm2_5: Vec<Option<f64>>,
+    pub pm10: Vec<Option<f64>>,
+    pub ozone: Vec<Option<f64>>,
+    pub nitrogen_dioxide: Vec<Option<f64>>,
+    pub european_aqi: Vec<Option<f64>>,
+    pub alder_pollen: Vec<Option<f64>>,
+    pub birch_pollen: Vec<Option<f64>>,
+    pub grass_pollen: Vec<Option<f64>>,
+    pub mugwort_pollen: Vec<Option<f64>>,
+    pub olive_pollen: Vec<Option<f64>>,
+    pub ragweed_pollen: Vec<Option<f64>>,
+}
+
+// Structs to deserialize open-meteo marine api results based of this kind of response:
+// https://marine-api.open-meteo.com/v1/marine?latitude=51.35&longitude=3.26667&daily=wave_height_max,wave_period_max,wave_direction_dominant,swell_wave_height_max&hourly=sea_surface_temperature&forecast_days=7&timezone=auto
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarineResponse {
+    pub daily: MarineDaily,
+    pub daily_units: MarineDailyUnits,
+    pub hourly: SeaTemperatureHourly,
+    pub hourly_units: SeaTemperatureUnits,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarineDailyUnits {
+    pub wave_height_max: String,
+    pub wave_period_max: String,
+    pub swell_wave_height_max: String,
+}
+
+/// Daily values, null away from the sea (and past the end of the wave models)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarineDaily {
+    pub time: Vec<String>,
+    pub wave_height_max: Vec<Option<f64>>,
+    pub wave_period_max: Vec<Option<f64>>,
+    pub wave_direction_dominant: Vec<Option<f64>>,
+    pub swell_wave_height_max: Vec<Option<f64>>,
+}
+
+/// The sea temperature is only given by hour
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeaTemperatureHourly {
+    pub time: Vec<String>,
+    pub sea_surface_temperature: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeaTemperatureUnits {
+    pub sea_surface_temperature: String,
+}
+
+// Structs to deserialize open-meteo ensemble api results based of this kind of response:
+// https://ensemble-api.open-meteo.com/v1/ensemble?latitude=50.8505&longitude=4.3488&daily=temperature_2m_min,temperature_2m_max,precipitation_sum&models=ecmwf_ifs025&forecast_days=7&timezone=auto
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnsembleResponse {
+    pub daily: EnsembleDaily,
+}
+
+/// Every member is its own field: "temperature_2m_max" (control run),
+/// "temperature_2m_max_member01", "temperature_2m_max_member02"...
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnsembleDaily {
+    pub time: Vec<String>,
+    #[serde(flatten)]
+    pub members: BTreeMap<String, Vec<Option<f64>>>,
+}
+
+// Structs to deserialize the open-meteo archive temperatures the climate normals are made of:
+// https://archive-api.open-meteo.com/v1/archive?latitude=50.8505&longitude=4.3488&start_date=2015-01-01&end_date=2024-12-31&daily=temperature_2m_min,temperature_2m_max,temperature_2m_mean&timezone=auto
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemperatureHistoryResponse {
+    pub daily: TemperatureHistoryDaily,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemperatureHistoryDaily {
+    pub time: Vec<String>,
+    pub temperature_2m_min: Vec<f64>,
+    pub temperature_2m_max: Vec<f64>,
+    pub temperature_2m_mean: Vec<f64>,
+}
+
 // Structs to deserialize open-meteo geocoding api results based of this kind of response:
 // https://geocoding-api.open-meteo.com/v1/search?name=Grimbergen&count=1&language=en&format=json
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GeoResponse {
     pub results: Option<Vec<GeoResult>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GeoResult {
     pub name: String,
     pub latitude: f64,
@@ -134,6 +893,64 @@ impl TryFrom<GeoResponse> for City {
             name: city_info.name.to_string(),
             lat: city_info.latitude,
             long: city_info.longitude,
+            coastal: false,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn models_response() -> ModelsResponse {
+        serde_json::from_value(json!({
+            "timezone": "Europe/Brussels",
+            "daily": {
+                "time": ["2026-10-18", "2026-10-19"],
+                "weather_code_icon_d2": [3, 61],
+                "temperature_2m_min_icon_d2": [6.0, 7.5],
+                "temperature_2m_max_icon_d2": [14.0, 12.5],
+                "temperature_2m_mean_icon_d2": [10.0, 10.0],
+                "precipitation_sum_icon_d2": [0.0, 4.2],
+                "precipitation_hours_icon_d2": [null, null],
+                "precipitation_probability_max_icon_d2": [null, null],
+                "sunrise_icon_d2": ["2026-10-18T08:12", "2026-10-19T08:14"],
+                "sunset_icon_d2": ["2026-10-18T18:41", "2026-10-19T18:39"],
+                "daylight_duration_icon_d2": [37740.0, 37500.0],
+                "sunshine_duration_icon_d2": [12000.0, null],
+                "wind_speed_10m_max_icon_d2": [null, 21.0],
+                "wind_gusts_10m_max_icon_d2": [null, null],
+                "wind_direction_10m_dominant_icon_d2": [null, 225.0]
+            },
+            "daily_units": {
+                "temperature_2m_max_icon_d2": "°C",
+                "sunrise_icon_d2": "iso8601",
+                "wind_speed_10m_max_icon_d2": "km/h"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn missing_model_values_stay_unknown() {
+        let forecast = models_response().forecast_of("icon_d2").unwrap();
+        let daily = &forecast.daily;
+        assert_eq!(daily.time.len(), 2);
+        assert_eq!(daily.precipitation_hours, [None, None]);
+        assert_eq!(daily.wind_speed_10m_max, [None, Some(21.0)]);
+        assert_eq!(daily.wind_gusts_10m_max, [None, None]);
+        assert_eq!(forecast.daily_units.wind_speed_10m_max, "km/h");
+    }
+
+    #[test]
+    fn model_forecasts_have_the_sun() {
+        let forecast = models_response().forecast_of("icon_d2").unwrap();
+        let daily = &forecast.daily;
+        assert_eq!(daily.sunrise, ["2026-10-18T08:12", "2026-10-19T08:14"]);
+        assert_eq!(daily.sunset, ["2026-10-18T18:41", "2026-10-19T18:39"]);
+        assert_eq!(daily.daylight_duration, [37740.0, 37500.0]);
+        assert_eq!(daily.sunshine_duration, [Some(12000.0), None]);
+        assert_eq!(forecast.daily_units.sunrise, "iso8601");
+    }
+}
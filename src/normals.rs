@@ -0,0 +1,260 @@
+use crate::db::{Store, StoredCity};
+use crate::models::{City, DayNormal, ForecastResponse, TemperatureHistoryResponse};
+use crate::open_meteo::get_city_temperature_history;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+
+// Climate normals: the usual temperatures of each calendar day, computed from the archive
+// over the last complete years and smoothed over a few days around the date (one year alone
+// is mostly noise). They are kept in the database and only computed again once a year.
+
+/// Years of archive the normals are computed from. A long archive request counts as many calls
+/// against the open-meteo quota, 10 years is a fair compromise.
+const NORMAL_YEARS: i32 = 10;
+/// Days on each side of a date averaged with it.
+const SMOOTHING_DAYS: usize = 7;
+/// Cached normals older than this are computed again.
+pub const NORMALS_MAX_AGE_DAYS: u32 = 365;
+/// Day of year indices, 29 February shares the 28th.
+const DAYS_IN_YEAR: usize = 365;
+
+/// Normals of every day of the year.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Normals {
+    pub first_year: i32,
+    pub last_year: i32,
+    /// Indexed by `day_of_year`
+    days: Vec<DayNormal>,
+}
+
+/// 0 for 1 January to 364 for 31 December, as if every year had 365 days.
+fn day_of_year(date: NaiveDate) -> usize {
+    let ordinal = date.ordinal0() as usize;
+    if date.leap_year() && ordinal >= 59 {
+        // 29 February (59) is counted with the 28th
+        ordinal - 1
+    } else {
+        ordinal
+    }
+}
+
+impl Normals {
+    /// Average of each calendar day over the years of `history`.
+    pub fn compute(
+        history: &TemperatureHistoryResponse,
+        first_year: i32,
+        last_year: i32,
+    ) -> Result<Self> {
+        let daily = &history.daily;
+        // sums of min, max, mean and the number of values, per day of year
+        let mut sums = vec![[0.0; 4]; DAYS_IN_YEAR];
+        for (id, date) in daily.time.iter().enumerate() {
+            let date: NaiveDate = date.parse().context("Invalid date in the archive")?;
+            let sum = &mut sums[day_of_year(date)];
+            sum[0] += daily.temperature_2m_min[id];
+            sum[1] += daily.temperature_2m_max[id];
+            sum[2] += daily.temperature_2m_mean[id];
+            sum[3] += 1.0;
+        }
+        if sums.iter().any(|sum| sum[3] == 0.0) {
+            bail!("The archive doesn't cover a whole year");
+        }
+
+        let days = (0..DAYS_IN_YEAR)
+            .map(|day| {
+                let mut total = [0.0; 4];
+                for offset in 0..=2 * SMOOTHING_DAYS {
+                    // around the year: late December is next to early January
+                    let other = (day + DAYS_IN_YEAR + offset - SMOOTHING_DAYS) % DAYS_IN_YEAR;
+                    for (t, s) in total.iter_mut().zip(sums[other]) {
+                        *t += s;
+                    }
+                }
+                DayNormal {
+                    min: total[0] / total[3],
+                    max: total[1] / total[3],
+                    mean: total[2] / total[3],
+                }
+            })
+            .collect();
+
+        Ok(Normals {
+            first_year,
+            last_year,
+            days,
+        })
+    }
+
+    /// Normal of a date (YYYY-MM-DD)
+    pub fn get(&self, date: &str) -> Option<DayNormal> {
+        let date: NaiveDate = date.parse().ok()?;
+        self.days.get(day_of_year(date)).copied()
+    }
+
+    /// Set the normals of each day of the forecast.
+    pub fn annotate(&self, forecast: &mut ForecastResponse) {
+        let normals = forecast
+            .daily
+            .time
+            .iter()
+            .map(|date| self.get(date))
+            .collect::<Option<Vec<_>>>();
+        forecast.normals = normals;
+    }
+}
+
+/// Normals of a stored city, from the database or computed from the archive (and saved).
+pub async fn for_city(store: &Store, stored: &StoredCity) -> Result<Normals> {
+    if let Some(normals) = store.normals(stored, NORMALS_MAX_AGE_DAYS)? {
+        return Ok(normals);
+    }
+    let normals = fetch(&stored.city).await?;
+    store.save_normals(stored, &normals)?;
+    Ok(normals)
+}
+
+/// Normals of several stored cities, in the same order: the ones missing from the database
+/// are computed at the same time (and saved).
+pub async fn for_cities(store: &Store, cities: &[StoredCity]) -> Vec<Result<Normals>> {
+    let mut normals = vec![];
+    let mut fetches = JoinSet::new();
+    for (id, stored) in cities.iter().enumerate() {
+        match store.normals(stored, NORMALS_MAX_AGE_DAYS) {
+            Ok(Some(cached)) => normals.push(Ok(cached)),
+            Ok(None) => {
+                let city = stored.city.clone();
+                fetches.spawn(async move { (id, fetch(&city).await) });
+                normals.push(Err(anyhow!(
+                    "Couldn't fetch the archive of {}",
+                    stored.city
+                )));
+            }
+            Err(e) => normals.push(Err(e)),
+        }
+    }
+
+    while let Some(fetched) = fetches.join_next().await {
+        // a panicking fetch keeps its error
+        let Ok((id, fetched)) = fetched else {
+            continue;
+        };
+        normals[id] = fetched.and_then(|fetched| {
+            store.save_normals(&cities[id], &fetched)?;
+            Ok(fetched)
+        });
+    }
+    normals
+}
+
+/// Normals computed from the archive of the last complete years.
+async fn fetch(city: &City) -> Result<Normals> {
+    let last_year = Local::now().year() - 1;
+    let first_year = last_year - NORMAL_YEARS + 1;
+    let (from, to) = NaiveDate::from_ymd_opt(first_year, 1, 1)
+        .zip(NaiveDate::from_ymd_opt(last_year, 12, 31))
+        .context("Invalid normals period")?;
+
+    let history = get_city_temperature_history(city, from, to).await?;
+    Normals::compute(&history, first_year, last_year)
+        .with_context(|| format!("Couldn't compute the normals of {}", city.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TemperatureHistoryDaily;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Archive of a whole year: 10 °C every day, `warm` days at 25 °C.
+    fn history(year: i32, warm: &[NaiveDate]) -> TemperatureHistoryResponse {
+        let dates: Vec<NaiveDate> = date(year, 1, 1)
+            .iter_days()
+            .take_while(|d| d.year() == year)
+            .collect();
+        let temperature = |d: &NaiveDate| if warm.contains(d) { 25.0 } else { 10.0 };
+        TemperatureHistoryResponse {
+            daily: TemperatureHistoryDaily {
+                time: dates.iter().map(NaiveDate::to_string).collect(),
+                temperature_2m_min: dates.iter().map(|d| temperature(d) - 5.0).collect(),
+                temperature_2m_max: dates.iter().map(|d| temperature(d) + 5.0).collect(),
+                temperature_2m_mean: dates.iter().map(temperature).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn leap_day_counts_with_28_february() {
+        assert_eq!(day_of_year(date(2024, 2, 28)), 58);
+        assert_eq!(day_of_year(date(2024, 2, 29)), 58);
+        assert_eq!(day_of_year(date(2024, 3, 1)), 59);
+        assert_eq!(day_of_year(date(2025, 3, 1)), 59);
+        assert_eq!(day_of_year(date(2024, 12, 31)), 364);
+        assert_eq!(day_of_year(date(2025, 12, 31)), 364);
+    }
+
+    #[test]
+    fn one_warm_day_is_spread_over_two_weeks() {
+        let normals = Normals::compute(&history(2025, &[date(2025, 6, 15)]), 2025, 2025).unwrap();
+        // 15 °C more over the 15 days around it
+        let warmer = |d: &str| normals.get(d).unwrap().mean - 10.0;
+        assert!((warmer("2025-06-15") - 1.0).abs() < 1e-9);
+        assert!((warmer("2025-06-08") - 1.0).abs() < 1e-9);
+        assert!((warmer("2025-06-22") - 1.0).abs() < 1e-9);
+        assert_eq!(warmer("2025-06-07"), 0.0);
+        assert_eq!(warmer("2025-06-23"), 0.0);
+
+        let normal = normals.get("2025-06-15").unwrap();
+        assert!((normal.min - 6.0).abs() < 1e-9);
+        assert!((normal.max - 16.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn smoothing_goes_around_the_year() {
+        let normals = Normals::compute(&history(2025, &[date(2025, 1, 1)]), 2025, 2025).unwrap();
+        let warmer = |d: &str| normals.get(d).unwrap().mean - 10.0;
+        assert!((warmer("2025-12-25") - 1.0).abs() < 1e-9);
+        assert_eq!(warmer("2025-12-24"), 0.0);
+    }
+
+    #[test]
+    fn leap_years_are_averaged_with_the_others() {
+        let mut leap = history(2024, &[date(2024, 2, 29)]);
+        let other = history(2025, &[]);
+        for (all, more) in [
+            (
+                &mut leap.daily.temperature_2m_min,
+                &other.daily.temperature_2m_min,
+            ),
+            (
+                &mut leap.daily.temperature_2m_max,
+                &other.daily.temperature_2m_max,
+            ),
+            (
+                &mut leap.daily.temperature_2m_mean,
+                &other.daily.temperature_2m_mean,
+            ),
+        ] {
+            all.extend(more);
+        }
+        leap.daily.time.extend(other.daily.time);
+
+        let normals = Normals::compute(&leap, 2024, 2025).unwrap();
+        // 29 February has the normal of the 28th, which had 3 values instead of 2
+        let normal = normals.get("2024-02-29").unwrap();
+        assert_eq!(normal.mean, normals.get("2025-02-28").unwrap().mean);
+        assert!((normal.mean - (10.0 * 30.0 + 25.0) / 31.0).abs() < 1e-9);
+        assert_eq!(normals.get("2025-01-01").unwrap().mean, 10.0);
+    }
+
+    #[test]
+    fn a_partial_archive_is_rejected() {
+        let mut partial = history(2025, &[]);
+        partial.daily.time.truncate(300);
+        assert!(Normals::compute(&partial, 2025, 2025).is_err());
+    }
+}
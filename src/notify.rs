@@ -0,0 +1,510 @@
+use crate::alerts::Alert;
+use crate::db::Store;
+use anyhow::{Context, Result, bail};
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+
+// Where fired alerts are sent, configured next to the rules:
+//
+//   [[sink]]
+//   type = "webhook"
+//   url = "https://hooks.slack.com/services/..."
+//   format = "slack"           # json (default), slack or teams
+//
+//   [[sink]]
+//   type = "email"
+//   server = "smtp.example.com"
+//   tls = "starttls"           # starttls (default), tls or none (plain, for local test servers)
+//   username = "me@example.com"
+//   password_env = "SMTP_PASSWORD"
+//   from = "deca-weather <me@example.com>"
+//   to = ["team@example.com"]
+//
+//   [[sink]]
+//   type = "desktop"           # runs notify-send, or `command` with the same arguments
+//
+// Each sink only gets an alert once: what was sent is recorded in the database per sink `name`
+// (the type by default), rule, city and date.
+
+/// Sent alerts are forgotten after this many days, they are long past by then.
+const NOTIFIED_RETENTION_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// `{"text": ..., "alerts": [...]}`
+    #[default]
+    Json,
+    /// Slack incoming webhook
+    Slack,
+    /// Microsoft Teams incoming webhook (MessageCard)
+    Teams,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    #[default]
+    Starttls,
+    Tls,
+    None,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Sink {
+    Webhook {
+        name: Option<String>,
+        url: String,
+        #[serde(default)]
+        format: WebhookFormat,
+    },
+    Email {
+        name: Option<String>,
+        server: String,
+        port: Option<u16>,
+        #[serde(default)]
+        tls: SmtpTls,
+        username: Option<String>,
+        /// Environment variable holding the password, to keep it out of the file.
+        password_env: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+    Desktop {
+        name: Option<String>,
+        command: Option<String>,
+    },
+}
+
+impl Sink {
+    /// Name used to remember what this sink was sent.
+    pub fn name(&self) -> &str {
+        match self {
+            Sink::Webhook { name, .. } => name.as_deref().unwrap_or("webhook"),
+            Sink::Email { name, .. } => name.as_deref().unwrap_or("email"),
+            Sink::Desktop { name, .. } => name.as_deref().unwrap_or("desktop"),
+        }
+    }
+
+    /// Send every alert in one message.
+    pub async fn send(&self, alerts: &[Alert]) -> Result<()> {
+        let title = match alerts {
+            [alert] => format!("deca-weather: {}", alert.rule),
+            _ => format!("deca-weather: {} weather alerts", alerts.len()),
+        };
+        let text = alerts
+            .iter()
+            .map(|alert| alert.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match self {
+            Sink::Webhook { url, format, .. } => {
+                let payload = match format {
+                    WebhookFormat::Json => json!({ "text": text, "alerts": alerts }),
+                    WebhookFormat::Slack => json!({ "text": format!("*{title}*\n{text}") }),
+                    WebhookFormat::Teams => json!({
+                        "@type": "MessageCard",
+                        "@context": "https://schema.org/extensions",
+                        "summary": title,
+                        "title": title,
+                        "text": text.replace('\n', "<br>"),
+                    }),
+                };
+                let response = reqwest::Client::new()
+                    .post(url)
+                    .json(&payload)
+                    .send()
+                    .await
+                    .with_context(|| format!("Failed to send webhook to {url}"))?;
+                if !response.status().is_success() {
+                    bail!("Webhook {url} returned: {}", response.status());
+                }
+            }
+            Sink::Email {
+                server,
+                port,
+                tls,
+                username,
+                password_env,
+                from,
+                to,
+                ..
+            } => {
+                let mut message = Message::builder()
+                    .from(from.parse().context("Invalid from address")?)
+                    .subject(title)
+                    .header(ContentType::TEXT_PLAIN);
+                for address in to {
+                    message = message.to(address
+                        .parse()
+                        .with_context(|| format!("Invalid address '{address}'"))?);
+                }
+                let message = message.body(text)?;
+
+                let mut transport = match tls {
+                    SmtpTls::Starttls => {
+                        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(server)?
+                    }
+                    SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(server)?,
+                    SmtpTls::None => {
+                        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(server)
+                    }
+                };
+                if let Some(port) = port {
+                    transport = transport.port(*port);
+                }
+                if let Some(username) = username {
+                    let password = match password_env {
+                        Some(var) => std::env::var(var)
+                            .with_context(|| format!("{var} isn't set for the SMTP password"))?,
+                        None => String::new(),
+                    };
+                    transport = transport.credentials(Credentials::new(username.clone(), password));
+                }
+                transport
+                    .build()
+                    .send(message)
+                    .await
+                    .with_context(|| format!("Failed to send email through {server}"))?;
+            }
+            Sink::Desktop { command, .. } => {
+                let command = command.as_deref().unwrap_or("notify-send");
+                let status = tokio::process::Command::new(command)
+                    .args([
+                        "--app-name=deca-weather",
+                        "--urgency=critical",
+                        &title,
+                        &text,
+                    ])
+                    .status()
+                    .await
+                    .with_context(|| format!("Couldn't run {command}"))?;
+                if !status.success() {
+                    bail!("{command} failed: {status}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Catch configuration mistakes when the file is loaded rather than when an alert fires.
+    fn check(&self) -> Result<()> {
+        match self {
+            Sink::Webhook { url, .. } => {
+                reqwest::Url::parse(url).with_context(|| format!("Invalid webhook url '{url}'"))?;
+            }
+            Sink::Email { from, to, .. } => {
+                from.parse::<Mailbox>()
+                    .with_context(|| format!("Invalid from address '{from}'"))?;
+                if to.is_empty() {
+                    bail!("No `to` address");
+                }
+                for address in to {
+                    address
+                        .parse::<Mailbox>()
+                        .with_context(|| format!("Invalid address '{address}'"))?;
+                }
+            }
+            Sink::Desktop { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+/// Check each sink. Sink names must be unique, they are how sent alerts are remembered.
+pub fn check_sinks(sinks: &[Sink]) -> Result<()> {
+    let mut names = HashSet::new();
+    for sink in sinks {
+        sink.check()
+            .with_context(|| format!("Sink '{}'", sink.name()))?;
+        if !names.insert(sink.name()) {
+            bail!(
+                "Two sinks are named '{}', give them a different `name`",
+                sink.name()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Send the alerts each sink hasn't been sent yet. Every sink is tried, even after a failure.
+/// Returns how many sinks were notified.
+pub async fn notify(store: &Store, sinks: &[Sink], alerts: &[Alert]) -> Result<usize> {
+    store.prune_notified(NOTIFIED_RETENTION_DAYS)?;
+
+    let mut notified = 0;
+    let mut failed = vec![];
+    for sink in sinks {
+        let mut new = vec![];
+        for alert in alerts {
+            if !store.was_notified(sink.name(), alert)? {
+                new.push(alert.clone());
+            }
+        }
+        if new.is_empty() {
+            continue;
+        }
+
+        match sink.send(&new).await {
+            Ok(()) => {
+                for alert in &new {
+                    store.mark_notified(sink.name(), alert)?;
+                }
+                notified += 1;
+            }
+            Err(e) => {
+                eprintln!("Sink '{}': {e:#}", sink.name());
+                failed.push(sink.name());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("Couldn't notify {}", failed.join(", "));
+    }
+    Ok(notified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestDir;
+    use axum::Router;
+    use axum::routing::post;
+    use std::path::Path;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    fn alert(rule: &str) -> Alert {
+        Alert {
+            rule: rule.to_string(),
+            city: "Gent".to_string(),
+            day: 1,
+            date: "2026-10-19".to_string(),
+            detail: "min -2.0 °C".to_string(),
+        }
+    }
+
+    fn webhook(url: String, format: WebhookFormat) -> Sink {
+        Sink::Webhook {
+            name: None,
+            url,
+            format,
+        }
+    }
+
+    fn email(to: &[&str]) -> Sink {
+        Sink::Email {
+            name: None,
+            server: "127.0.0.1".to_string(),
+            port: None,
+            tls: SmtpTls::None,
+            username: None,
+            password_env: None,
+            from: "deca-weather <alerts@example.com>".to_string(),
+            to: to.iter().map(|address| address.to_string()).collect(),
+        }
+    }
+
+    /// Script writing its arguments to `out`, one per line (appended, to count the runs)
+    #[cfg(unix)]
+    fn stub_command(dir: &Path, out: &Path) -> String {
+        use std::os::unix::fs::PermissionsExt;
+        let script = dir.join("notify-send");
+        let content = format!("#!/bin/sh\nprintf '%s\\n' \"$@\" >> '{}'\n", out.display());
+        std::fs::write(&script, content).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script.display().to_string()
+    }
+
+    /// Local webhook receiver, each body received is sent on the channel
+    async fn webhook_stand_in() -> (String, mpsc::UnboundedReceiver<serde_json::Value>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/hook",
+            post(
+                move |axum::Json(body): axum::Json<serde_json::Value>| async move {
+                    tx.send(body).unwrap();
+                },
+            ),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, rx)
+    }
+
+    /// Minimal SMTP server taking one message, returns its port and the message data
+    async fn smtp_stand_in() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (read, mut write) = socket.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+            let mut data = String::new();
+            let mut in_data = false;
+            while let Some(line) = lines.next_line().await.unwrap() {
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        write.write_all(b"250 queued\r\n").await.unwrap();
+                    } else {
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    continue;
+                }
+                let command = line.to_uppercase();
+                let reply: &[u8] = if command.starts_with("DATA") {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if command.starts_with("QUIT") {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            data
+        });
+        (port, server)
+    }
+
+    #[tokio::test]
+    async fn webhook_payloads() {
+        let (url, mut bodies) = webhook_stand_in().await;
+        let alerts = [alert("frost")];
+
+        webhook(url.clone(), WebhookFormat::Json)
+            .send(&alerts)
+            .await
+            .unwrap();
+        let body = bodies.recv().await.unwrap();
+        assert_eq!(body["text"], "frost: Gent on 2026-10-19: min -2.0 °C");
+        assert_eq!(body["alerts"][0]["rule"], "frost");
+        assert_eq!(body["alerts"][0]["day"], 1);
+
+        webhook(url.clone(), WebhookFormat::Slack)
+            .send(&alerts)
+            .await
+            .unwrap();
+        let body = bodies.recv().await.unwrap();
+        assert_eq!(
+            body,
+            json!({ "text": "*deca-weather: frost*\nfrost: Gent on 2026-10-19: min -2.0 °C" })
+        );
+
+        webhook(url, WebhookFormat::Teams)
+            .send(&[alert("frost"), alert("storm")])
+            .await
+            .unwrap();
+        let body = bodies.recv().await.unwrap();
+        assert_eq!(body["@type"], "MessageCard");
+        assert_eq!(body["title"], "deca-weather: 2 weather alerts");
+        assert_eq!(
+            body["text"],
+            "frost: Gent on 2026-10-19: min -2.0 °C<br>storm: Gent on 2026-10-19: min -2.0 °C"
+        );
+    }
+
+    #[tokio::test]
+    async fn webhook_error_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/missing", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, Router::new()).await });
+
+        let e = webhook(url, WebhookFormat::Json)
+            .send(&[alert("frost")])
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("404"), "{e}");
+    }
+
+    #[tokio::test]
+    async fn email_through_local_smtp() {
+        let (port, server) = smtp_stand_in().await;
+        let mut sink = email(&["team@example.com"]);
+        if let Sink::Email { port: p, .. } = &mut sink {
+            *p = Some(port);
+        }
+
+        sink.send(&[alert("frost")]).await.unwrap();
+        let data = server.await.unwrap();
+        assert!(data.contains("Subject: deca-weather: frost"), "{data}");
+        assert!(data.contains("To: team@example.com"), "{data}");
+        assert!(data.contains("frost: Gent on 2026-10-19"), "{data}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn desktop_command_arguments() {
+        let dir = TestDir::new("desktop");
+        let out = dir.join("args");
+        let sink = Sink::Desktop {
+            name: None,
+            command: Some(stub_command(dir.path(), &out)),
+        };
+
+        sink.send(&[alert("frost")]).await.unwrap();
+        let args = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(
+            args.lines().collect::<Vec<_>>(),
+            [
+                "--app-name=deca-weather",
+                "--urgency=critical",
+                "deca-weather: frost",
+                "frost: Gent on 2026-10-19: min -2.0 °C",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn repeat_alerts_are_not_sent_again() {
+        let dir = TestDir::new("dedup");
+        let out = dir.join("args");
+        let store = Store::open(&dir.join("test.db")).unwrap();
+        let sinks = [Sink::Desktop {
+            name: None,
+            command: Some(stub_command(dir.path(), &out)),
+        }];
+        let alerts = [alert("frost")];
+
+        assert!(!store.was_notified("desktop", &alerts[0]).unwrap());
+        assert_eq!(notify(&store, &sinks, &alerts).await.unwrap(), 1);
+        assert!(store.was_notified("desktop", &alerts[0]).unwrap());
+        // nothing new: the command isn't run again
+        assert_eq!(notify(&store, &sinks, &alerts).await.unwrap(), 0);
+        let runs = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(runs.matches("--app-name=deca-weather").count(), 1);
+
+        // another sink still gets it
+        assert!(!store.was_notified("other", &alerts[0]).unwrap());
+        store.mark_notified("other", &alerts[0]).unwrap();
+        assert!(store.was_notified("other", &alerts[0]).unwrap());
+    }
+
+    #[test]
+    fn invalid_sinks_are_rejected() {
+        assert!(check_sinks(&[email(&["team@example.com"])]).is_ok());
+        assert!(check_sinks(&[email(&[])]).is_err());
+        assert!(check_sinks(&[email(&["not an address"])]).is_err());
+        assert!(check_sinks(&[webhook("not a url".to_string(), WebhookFormat::Json)]).is_err());
+        let twice = [
+            webhook("http://127.0.0.1/hook".to_string(), WebhookFormat::Json),
+            webhook("http://127.0.0.1/other".to_string(), WebhookFormat::Slack),
+        ];
+        assert!(check_sinks(&twice).is_err());
+    }
+}
@@ -1,50 +1,307 @@
 use anyhow::{Context, Result, bail};
+use chrono::{Days, Local, NaiveDate};
+use serde::de::DeserializeOwned;
+use std::time::Instant;
 
-use crate::models::{City, ForecastResponse, GeoResponse};
+use crate::args::WeatherModel;
+use crate::metrics::record_upstream;
+use crate::models::{
+    AirQualityResponse, City, EnsembleResponse, ForecastResponse, GeoResponse, HourlyResponse,
+    MarineResponse, ModelsResponse, TemperatureHistoryResponse,
+};
 
-// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&daily=weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean&timezone=auto
+// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&daily=weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean,precipitation_sum,precipitation_hours,precipitation_probability_max,sunrise,sunset,daylight_duration,sunshine_duration,wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant&timezone=auto
 
 const OPEN_METEO_BASE_URL: &str = "https://api.open-meteo.com/v1/forecast";
 const OPEN_GEOCODE_BASE_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const OPEN_METEO_ARCHIVE_URL: &str = "https://archive-api.open-meteo.com/v1/archive";
+const OPEN_METEO_AIR_QUALITY_URL: &str = "https://air-quality-api.open-meteo.com/v1/air-quality";
+const OPEN_METEO_MARINE_URL: &str = "https://marine-api.open-meteo.com/v1/marine";
+const OPEN_METEO_ENSEMBLE_URL: &str = "https://ensemble-api.open-meteo.com/v1/ensemble";
+
+/// Daily variables asked for every forecast.
+pub const DAILY_VARIABLES: &str = "weather_code,temperature_2m_min,temperature_2m_max,\
+    temperature_2m_mean,precipitation_sum,precipitation_hours,precipitation_probability_max,\
+    sunrise,sunset,daylight_duration,sunshine_duration,\
+    wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant";
+/// Same as `DAILY_VARIABLES` without the probability, the archive only has what happened.
+const ARCHIVE_DAILY_VARIABLES: &str = "weather_code,temperature_2m_min,temperature_2m_max,\
+    temperature_2m_mean,precipitation_sum,precipitation_hours,\
+    sunrise,sunset,daylight_duration,sunshine_duration,\
+    wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant";
+/// What the climate normals are computed from, years of it: only the temperatures.
+const NORMALS_DAILY_VARIABLES: &str = "temperature_2m_min,temperature_2m_max,temperature_2m_mean";
+/// Same as `DAILY_VARIABLES`: each model gives what it has, null for the rest.
+const MODEL_DAILY_VARIABLES: &str = DAILY_VARIABLES;
+const HOURLY_VARIABLES: &str = "temperature_2m,precipitation_probability,precipitation,\
+    weather_code,wind_speed_10m,wind_direction_10m,wind_gusts_10m";
+const AIR_QUALITY_VARIABLES: &str = "pm2_5,pm10,ozone,nitrogen_dioxide,european_aqi,\
+    alder_pollen,birch_pollen,grass_pollen,mugwort_pollen,olive_pollen,ragweed_pollen";
+/// Daily variables of the ensemble, the `ensemble` module knows their names.
+pub const ENSEMBLE_DAILY_VARIABLES: &str =
+    "temperature_2m_min,temperature_2m_max,precipitation_sum";
+/// ECMWF IFS ensemble: 51 members, 15 days, global.
+const ENSEMBLE_MODEL: &str = "ecmwf_ifs025";
+const MARINE_DAILY_VARIABLES: &str =
+    "wave_height_max,wave_period_max,wave_direction_dominant,swell_wave_height_max";
+
+/// Number of forecast days open-meteo returns when not asked otherwise.
+pub const DEFAULT_FORECAST_DAYS: u8 = 7;
+/// Longest forecast open-meteo can give.
+pub const MAX_FORECAST_DAYS: u8 = 16;
+/// Longest air quality forecast (pollen only goes 4 days ahead).
+pub const MAX_AIR_QUALITY_DAYS: u8 = 7;
+/// Longest marine forecast, the wave models don't go further.
+pub const MAX_MARINE_DAYS: u8 = 8;
+/// Longest ensemble forecast of the model we use.
+pub const MAX_ENSEMBLE_DAYS: u8 = 15;
+/// Longest look ahead of the rain command, hourly probabilities get vague after two days.
+pub const MAX_RAIN_HOURS: u16 = 48;
+/// The archive is a few days behind, it answers null for the days it doesn't have yet.
+pub const ARCHIVE_DELAY_DAYS: u64 = 5;
 
 /// Helper to get city forecast by using City struct directly
 pub async fn get_city_forecast(city: &City) -> Result<ForecastResponse> {
-    get_forecast(city.lat, city.long)
+    get_city_forecast_days(city, DEFAULT_FORECAST_DAYS).await
+}
+
+/// Same as `get_city_forecast` but for a given number of days (1 to 16)
+pub async fn get_city_forecast_days(city: &City, days: u8) -> Result<ForecastResponse> {
+    get_forecast(city.lat, city.long, days)
         .await
         .with_context(|| format!("Failed to fetch forecast for city {}", city.name))
 }
 
 /// Get city forecast by geocode (lat/long)
-pub async fn get_forecast(lat: f64, long: f64) -> Result<ForecastResponse> {
+pub async fn get_forecast(lat: f64, long: f64, days: u8) -> Result<ForecastResponse> {
     // building params for the query (list of tuples)
     let params = [
         ("latitude", lat.to_string()),
         ("longitude", long.to_string()),
-        (
-            "daily",
-            "weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean".to_string(),
-        ),
+        ("forecast_days", days.to_string()),
+        ("daily", DAILY_VARIABLES.to_string()),
         ("timezone", "auto".to_string()),
     ];
 
-    let response = reqwest::Client::new()
-        .get(OPEN_METEO_BASE_URL)
-        .query(&params)
-        .send()
+    fetch_json(OPEN_METEO_BASE_URL, &params, "open-meteo").await
+}
+
+// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&daily=weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean,precipitation_sum,precipitation_hours,precipitation_probability_max,sunrise,sunset,daylight_duration,sunshine_duration,wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant&models=icon_d2&timezone=auto
+
+/// Forecast of a city from one weather model
+pub async fn get_city_forecast_model(
+    city: &City,
+    days: u8,
+    model: WeatherModel,
+) -> Result<ForecastResponse> {
+    get_forecast_model(city.lat, city.long, days, model)
         .await
-        .context("Failed to send request to open-meteo")?;
+        .with_context(|| format!("Failed to fetch {model} forecast for city {}", city.name))
+}
 
-    let status = response.status();
-    if !status.is_success() {
-        bail!("No success: Opent-meteo returned: {status}");
+/// Forecast by geocode from one weather model, the usual `get_forecast` for best_match.
+/// Short range models give fewer days than asked.
+pub async fn get_forecast_model(
+    lat: f64,
+    long: f64,
+    days: u8,
+    model: WeatherModel,
+) -> Result<ForecastResponse> {
+    if model == WeatherModel::BestMatch {
+        return get_forecast(lat, long, days).await;
     }
+    get_models(lat, long, days, &[model])
+        .await?
+        .forecast_of(model.api_name())
+}
 
-    let forecast = response
-        .json::<ForecastResponse>()
+/// Forecasts of several weather models in one request, the models without data for the
+/// place are left out
+pub async fn get_city_forecast_models(
+    city: &City,
+    days: u8,
+    models: &[WeatherModel],
+) -> Result<Vec<(WeatherModel, ForecastResponse)>> {
+    let response = get_models(city.lat, city.long, days, models)
         .await
-        .context("Couldn't deserialize open-meteo response")?;
+        .with_context(|| format!("Failed to fetch model forecasts for city {}", city.name))?;
+    Ok(models
+        .iter()
+        .filter_map(|&model| Some((model, response.forecast_of(model.api_name()).ok()?)))
+        .collect())
+}
+
+async fn get_models(
+    lat: f64,
+    long: f64,
+    days: u8,
+    models: &[WeatherModel],
+) -> Result<ModelsResponse> {
+    let names: Vec<&str> = models.iter().map(|model| model.api_name()).collect();
+    let params = [
+        ("latitude", lat.to_string()),
+        ("longitude", long.to_string()),
+        ("forecast_days", days.to_string()),
+        ("daily", MODEL_DAILY_VARIABLES.to_string()),
+        ("models", names.join(",")),
+        ("timezone", "auto".to_string()),
+    ];
+
+    fetch_json(OPEN_METEO_BASE_URL, &params, "open-meteo").await
+}
 
-    Ok(forecast)
+// https://api.open-meteo.com/v1/forecast?latitude=50.8505&longitude=4.3488&hourly=temperature_2m,precipitation_probability,precipitation,weather_code,wind_speed_10m,wind_direction_10m,wind_gusts_10m&forecast_hours=24&timezone=auto
+
+/// Hourly forecast of a city for the next `hours` hours
+pub async fn get_city_hourly_forecast(city: &City, hours: u16) -> Result<HourlyResponse> {
+    get_hourly_forecast(city.lat, city.long, hours)
+        .await
+        .with_context(|| format!("Failed to fetch hourly forecast for city {}", city.name))
+}
+
+/// Get the hourly forecast for the next `hours` hours, starting from the current hour
+pub async fn get_hourly_forecast(lat: f64, long: f64, hours: u16) -> Result<HourlyResponse> {
+    let params = [
+        ("latitude", lat.to_string()),
+        ("longitude", long.to_string()),
+        ("forecast_hours", hours.to_string()),
+        ("hourly", HOURLY_VARIABLES.to_string()),
+        ("timezone", "auto".to_string()),
+    ];
+
+    fetch_json(OPEN_METEO_BASE_URL, &params, "open-meteo").await
+}
+
+// https://archive-api.open-meteo.com/v1/archive?latitude=50.8505&longitude=4.3488&start_date=2024-10-01&end_date=2024-10-07&daily=weather_code,temperature_2m_min,temperature_2m_max,temperature_2m_mean,precipitation_sum,precipitation_hours,sunrise,sunset,daylight_duration,sunshine_duration,wind_speed_10m_max,wind_gusts_10m_max,wind_direction_10m_dominant&timezone=auto
+
+/// Past weather of a city between two dates (included), same shape as a forecast
+pub async fn get_city_history(
+    city: &City,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<ForecastResponse> {
+    get_history(city.lat, city.long, from, to)
+        .await
+        .with_context(|| format!("Failed to fetch past weather for city {}", city.name))
+}
+
+/// Get the past daily weather by geocode, from the archive (reanalysis, a few days behind)
+pub async fn get_history(
+    lat: f64,
+    long: f64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<ForecastResponse> {
+    let params = [
+        ("latitude", lat.to_string()),
+        ("longitude", long.to_string()),
+        ("start_date", from.to_string()),
+        ("end_date", to.to_string()),
+        ("daily", ARCHIVE_DAILY_VARIABLES.to_string()),
+        ("timezone", "auto".to_string()),
+    ];
+
+    fetch_json(OPEN_METEO_ARCHIVE_URL, &params, "open-meteo archive").await
+}
+
+// https://archive-api.open-meteo.com/v1/archive?latitude=50.8505&longitude=4.3488&start_date=2015-01-01&end_date=2024-12-31&daily=temperature_2m_min,temperature_2m_max,temperature_2m_mean&timezone=auto
+
+/// Past daily temperatures of a city between two dates (included), for the climate normals
+pub async fn get_city_temperature_history(
+    city: &City,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<TemperatureHistoryResponse> {
+    let params = [
+        ("latitude", city.lat.to_string()),
+        ("longitude", city.long.to_string()),
+        ("start_date", from.to_string()),
+        ("end_date", to.to_string()),
+        ("daily", NORMALS_DAILY_VARIABLES.to_string()),
+        ("timezone", "auto".to_string()),
+    ];
+
+    fetch_json(OPEN_METEO_ARCHIVE_URL, &params, "open-meteo archive")
+        .await
+        .with_context(|| format!("Failed to fetch past temperatures for city {}", city.name))
+}
+
+/// Most recent day the archive has
+pub fn latest_archive_day() -> NaiveDate {
+    Local::now().date_naive() - Days::new(ARCHIVE_DELAY_DAYS)
+}
+
+// https://air-quality-api.open-meteo.com/v1/air-quality?latitude=50.8505&longitude=4.3488&hourly=pm2_5,pm10,ozone,nitrogen_dioxide,european_aqi,birch_pollen&forecast_days=5&timezone=auto
+
+/// Hourly air quality and pollen of a city for the next `days` days (1 to 7)
+pub async fn get_city_air_quality(city: &City, days: u8) -> Result<AirQualityResponse> {
+    get_air_quality(city.lat, city.long, days)
+        .await
+        .with_context(|| format!("Failed to fetch air quality for city {}", city.name))
+}
+
+/// Get the hourly air quality forecast by geocode, starting today
+pub async fn get_air_quality(lat: f64, long: f64, days: u8) -> Result<AirQualityResponse> {
+    let params = [
+        ("latitude", lat.to_string()),
+        ("longitude", long.to_string()),
+        ("forecast_days", days.to_string()),
+        ("hourly", AIR_QUALITY_VARIABLES.to_string()),
+        ("timezone", "auto".to_string()),
+    ];
+
+    fetch_json(
+        OPEN_METEO_AIR_QUALITY_URL,
+        &params,
+        "open-meteo air quality",
+    )
+    .await
+}
+
+// https://marine-api.open-meteo.com/v1/marine?latitude=51.35&longitude=3.26667&daily=wave_height_max,wave_period_max,wave_direction_dominant,swell_wave_height_max&hourly=sea_surface_temperature&forecast_days=7&timezone=auto
+
+/// Waves, swell and sea temperature near a city for the next `days` days (1 to 8)
+pub async fn get_city_marine(city: &City, days: u8) -> Result<MarineResponse> {
+    get_marine(city.lat, city.long, days)
+        .await
+        .with_context(|| format!("Failed to fetch marine forecast for city {}", city.name))
+}
+
+/// Get the marine forecast by geocode, starting today
+pub async fn get_marine(lat: f64, long: f64, days: u8) -> Result<MarineResponse> {
+    let params = [
+        ("latitude", lat.to_string()),
+        ("longitude", long.to_string()),
+        ("forecast_days", days.to_string()),
+        ("daily", MARINE_DAILY_VARIABLES.to_string()),
+        ("hourly", "sea_surface_temperature".to_string()),
+        ("timezone", "auto".to_string()),
+    ];
+
+    fetch_json(OPEN_METEO_MARINE_URL, &params, "open-meteo marine").await
+}
+
+// https://ensemble-api.open-meteo.com/v1/ensemble?latitude=50.8505&longitude=4.3488&daily=temperature_2m_min,temperature_2m_max,precipitation_sum&models=ecmwf_ifs025&forecast_days=7&timezone=auto
+
+/// Every ensemble member of a city for the next `days` days (1 to 15)
+pub async fn get_city_ensemble(city: &City, days: u8) -> Result<EnsembleResponse> {
+    get_ensemble(city.lat, city.long, days)
+        .await
+        .with_context(|| format!("Failed to fetch ensemble forecast for city {}", city.name))
+}
+
+/// Get the ensemble forecast by geocode, starting today
+pub async fn get_ensemble(lat: f64, long: f64, days: u8) -> Result<EnsembleResponse> {
+    let params = [
+        ("latitude", lat.to_string()),
+        ("longitude", long.to_string()),
+        ("forecast_days", days.to_string()),
+        ("daily", ENSEMBLE_DAILY_VARIABLES.to_string()),
+        ("models", ENSEMBLE_MODEL.to_string()),
+        ("timezone", "auto".to_string()),
+    ];
+
+    fetch_json(OPEN_METEO_ENSEMBLE_URL, &params, "open-meteo ensemble").await
 }
 
 // https://geocoding-api.open-meteo.com/v1/search?name=Grimbergen&count=1&language=en
@@ -52,24 +309,47 @@ pub async fn get_forecast(lat: f64, long: f64) -> Result<ForecastResponse> {
 /// Get city geocoding info (lat/long)
 pub async fn get_geocode(name: &str) -> Result<GeoResponse> {
     // building params for the query (list of tuples)
-    let params = [("name", name.trim()), ("count", "1"), ("language", "en")];
+    let params = [
+        ("name", name.trim().to_string()),
+        ("count", "1".to_string()),
+        ("language", "en".to_string()),
+    ];
+
+    fetch_json(OPEN_GEOCODE_BASE_URL, &params, "open-meteo geocoding").await
+}
 
+/// Send the GET request and deserialize the JSON answer, `api` is used in error messages
+/// and as the label of the upstream metrics
+async fn fetch_json<T: DeserializeOwned>(
+    url: &str,
+    params: &[(&str, String)],
+    api: &str,
+) -> Result<T> {
+    let start = Instant::now();
+    let result = send_request(url, params, api).await;
+    record_upstream(api, start.elapsed(), result.is_ok());
+    result
+}
+
+async fn send_request<T: DeserializeOwned>(
+    url: &str,
+    params: &[(&str, String)],
+    api: &str,
+) -> Result<T> {
     let response = reqwest::Client::new()
-        .get(OPEN_GEOCODE_BASE_URL)
-        .query(&params)
+        .get(url)
+        .query(params)
         .send()
         .await
-        .context("Failed to send request to open-meteo geocoding api")?;
+        .with_context(|| format!("Failed to send request to {api}"))?;
 
     let status = response.status();
     if !status.is_success() {
-        bail!("No success: Opent-meteo geocoding returned: {status}");
+        bail!("No success: {api} returned: {status}");
     }
 
-    let geocode = response
-        .json::<GeoResponse>()
+    response
+        .json::<T>()
         .await
-        .context("Couldn't deserialize open-meteo geocoding response")?;
-
-    Ok(geocode)
+        .with_context(|| format!("Couldn't deserialize {api} response"))
 }
@@ -0,0 +1,120 @@
+use crate::display::RenderOptions;
+use crate::models::HourlyResponse;
+use crate::table::print_rows;
+use crate::wind;
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, TimeDelta};
+
+// "Will it rain?": the hours of the hourly forecast where rain is likely, grouped in windows
+// of consecutive hours.
+
+/// Probability (%) from which an hour counts as rainy.
+const RAIN_LIKELY_PROBABILITY: u8 = 50;
+/// Precipitation (mm) counted as rain for the models without probabilities.
+const RAIN_THRESHOLD: f64 = 0.1;
+
+/// Consecutive hours of likely rain, `end` excluded.
+#[derive(Debug)]
+pub struct RainWindow {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub precipitation: f64,
+    pub max_probability: Option<u8>,
+    /// Strongest wind of the window and where it comes from
+    pub wind_speed: f64,
+    pub wind_direction: f64,
+}
+
+fn is_rainy(probability: Option<u8>, precipitation: f64) -> bool {
+    match probability {
+        Some(p) => p >= RAIN_LIKELY_PROBABILITY,
+        None => precipitation >= RAIN_THRESHOLD,
+    }
+}
+
+/// "2026-10-18T14:00" in the local time of the place
+fn parse_hour(time: &str) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M")
+        .with_context(|| format!("Invalid time in the hourly forecast: {time}"))
+}
+
+/// The windows of likely rain, in order.
+pub fn windows(forecast: &HourlyResponse) -> Result<Vec<RainWindow>> {
+    let h = &forecast.hourly;
+    let mut windows: Vec<RainWindow> = vec![];
+    let mut extends_last = false;
+    for (id, time) in h.time.iter().enumerate() {
+        let (probability, precipitation) = (h.precipitation_probability[id], h.precipitation[id]);
+        let (wind_speed, wind_direction) = (h.wind_speed_10m[id], h.wind_direction_10m[id]);
+        if !is_rainy(probability, precipitation) {
+            extends_last = false;
+            continue;
+        }
+
+        let start = parse_hour(time)?;
+        let end = start + TimeDelta::hours(1);
+        match windows.last_mut() {
+            Some(window) if extends_last => {
+                window.end = end;
+                window.precipitation += precipitation;
+                window.max_probability = window.max_probability.max(probability);
+                if wind_speed > window.wind_speed {
+                    window.wind_speed = wind_speed;
+                    window.wind_direction = wind_direction;
+                }
+            }
+            _ => windows.push(RainWindow {
+                start,
+                end,
+                precipitation,
+                max_probability: probability,
+                wind_speed,
+                wind_direction,
+            }),
+        }
+        extends_last = true;
+    }
+    Ok(windows)
+}
+
+/// One row per window of likely rain, or the driest answer there is.
+pub fn print_rain(forecast: &HourlyResponse, opts: &RenderOptions) -> Result<()> {
+    let hours = forecast.len();
+    let windows = windows(forecast)?;
+    if windows.is_empty() {
+        let h = &forecast.hourly;
+        let highest = h
+            .precipitation_probability
+            .iter()
+            .zip(&h.time)
+            .filter_map(|(p, time)| Some(((*p)?, time)))
+            .max_by_key(|(p, _)| *p);
+        match highest {
+            Some((p, time)) if p > 0 => println!(
+                "   No rain expected in the next {hours} h (highest chance {p}% at {})",
+                parse_hour(time)?.format("%a %H:%M")
+            ),
+            _ => println!("   No rain expected in the next {hours} h"),
+        }
+        return Ok(());
+    }
+
+    let unit = &forecast.hourly_units.precipitation;
+    let wind_unit = &forecast.hourly_units.wind_speed_10m;
+    let rows: Vec<Vec<String>> = windows
+        .iter()
+        .map(|w| {
+            vec![
+                w.start.format("%a %H:%M").to_string(),
+                w.end.format("%a %H:%M").to_string(),
+                format!("{:.1} {unit}", w.precipitation),
+                w.max_probability
+                    .map_or("-".to_string(), |p| format!("{p}%")),
+                wind::format_wind(w.wind_speed, w.wind_direction, wind_unit, opts.unicode),
+            ]
+        })
+        .collect();
+    let headers = ["From", "To", "Rain", "Chance", "Wind"];
+    print_rows(&headers, &rows, &[0, 1, 4], opts);
+    Ok(())
+}
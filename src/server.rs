@@ -0,0 +1,489 @@
+use crate::cache::{CacheKey, Cached, TtlCache};
+use crate::db::{CityError, Store, StoredCity, validate_coordinates};
+use crate::ensemble;
+use crate::models::{City, ForecastResponse};
+use crate::open_meteo::{
+    DAILY_VARIABLES, DEFAULT_FORECAST_DAYS, MAX_ENSEMBLE_DAYS, MAX_FORECAST_DAYS, get_ensemble,
+    get_forecast, get_geocode,
+};
+use crate::web;
+use crate::wmo;
+use anyhow::{Context, Result};
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+// JSON API on top of the open_meteo functions and the city database:
+//   GET    /cities                       stored cities
+//   POST   /cities                       add a city {name, lat?, long?} (geocoded without coordinates)
+//   GET    /cities/{id}                  one stored city
+//   PATCH  /cities/{id}                  change the name and/or coordinates of a city
+//   DELETE /cities/{id}                  remove a city
+//   GET    /cities/{name}/forecast?days= forecast of a stored city
+//   GET    /forecast?lat=&long=&days=    forecast of any location
+//   GET    /geocode?name=                coordinates of a city
+//   GET    /openapi.json                 OpenAPI 3 description of the routes above
+//   GET    /docs                         Swagger UI page for /openapi.json
+//   GET    /                             web frontend (see the web module)
+//
+// Forecasts are cached (see the cache module) and sent with Cache-Control and ETag headers,
+// so clients can revalidate with If-None-Match and get an empty 304 back.
+
+/// Shared between the handlers, the database is the same one the CLI uses.
+#[derive(Clone)]
+pub struct AppState {
+    store: Arc<Mutex<Store>>,
+    forecasts: Arc<TtlCache<ForecastResponse>>,
+}
+
+impl AppState {
+    /// `cache_ttl`: how long an upstream forecast is reused.
+    pub fn new(store: Store, cache_ttl: Duration) -> Self {
+        AppState {
+            store: Arc::new(Mutex::new(store)),
+            forecasts: Arc::new(TtlCache::new(cache_ttl)),
+        }
+    }
+
+    /// Lock the database (never hold it across an .await)
+    fn store(&self) -> Result<MutexGuard<'_, Store>, ApiError> {
+        self.store
+            .lock()
+            .map_err(|_| ApiError::Internal(anyhow::anyhow!("Database lock poisoned")))
+    }
+}
+
+/// Start the HTTP server and serve until the process is stopped.
+pub async fn serve(state: AppState, addr: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Couldn't listen on {addr}"))?;
+    println!("Listening on http://{addr}");
+
+    axum::serve(listener, router(state))
+        .await
+        .context("HTTP server error")
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/cities", get(list_cities).post(create_city))
+        .route(
+            "/cities/{city}",
+            get(get_city).patch(update_city).delete(delete_city),
+        )
+        .route("/cities/{city}/forecast", get(city_forecast))
+        .route("/forecast", get(forecast))
+        .route("/geocode", get(geocode))
+        .route("/openapi.json", get(openapi))
+        .merge(web::router())
+        .with_state(state)
+}
+
+/// OpenAPI document, generated from the `#[utoipa::path]` handlers and the `ToSchema` models.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "deca-weather", description = "Weather forecasts from open-meteo for your cities"),
+    paths(
+        list_cities,
+        create_city,
+        get_city,
+        update_city,
+        delete_city,
+        city_forecast,
+        forecast,
+        geocode
+    ),
+    tags((name = "cities", description = "Cities stored in the local database"),
+        (name = "forecast", description = "Forecasts, cached server-side"))
+)]
+pub struct ApiDoc;
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Errors returned as `{"error": "..."}` with a matching status code.
+#[derive(Debug)]
+pub enum ApiError {
+    /// Invalid query parameters (400).
+    BadRequest(String),
+    /// Unknown city (404).
+    NotFound(String),
+    /// City name already taken (409).
+    Conflict(String),
+    /// open-meteo failed or is unreachable (502).
+    Upstream(anyhow::Error),
+    /// Database failure (500).
+    Internal(anyhow::Error),
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    #[schema(example = "No city with id 42")]
+    pub error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            ApiError::Upstream(e) => (StatusCode::BAD_GATEWAY, format!("{e:#}")),
+            ApiError::Internal(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}")),
+        };
+        (status, Json(ErrorBody { error })).into_response()
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::BadRequest(rejection.body_text())
+    }
+}
+
+impl From<CityError> for ApiError {
+    fn from(e: CityError) -> Self {
+        match e {
+            CityError::Duplicate(_) => ApiError::Conflict(e.to_string()),
+            _ => ApiError::BadRequest(e.to_string()),
+        }
+    }
+}
+
+/// Errors coming from the database: invalid cities are the client's fault, the rest is ours.
+/// (open-meteo errors are mapped explicitly with `ApiError::Upstream`)
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<CityError>() {
+            Ok(city_error) => city_error.into(),
+            Err(e) => ApiError::Internal(e),
+        }
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DaysQuery {
+    /// Number of forecast days, 1 to 16 (default 7)
+    #[param(minimum = 1, maximum = 16)]
+    pub days: Option<u8>,
+    /// Add the ensemble spread (p10, p50, p90) of each day, for the first 15 days
+    #[serde(default)]
+    pub ensemble: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ForecastQuery {
+    /// Latitude, -90 to 90
+    pub lat: f64,
+    /// Longitude, -180 to 180
+    pub long: f64,
+    /// Number of forecast days, 1 to 16 (default 7)
+    #[param(minimum = 1, maximum = 16)]
+    pub days: Option<u8>,
+    /// Add the ensemble spread (p10, p50, p90) of each day, for the first 15 days
+    #[serde(default)]
+    pub ensemble: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GeocodeQuery {
+    /// City name to look up
+    pub name: String,
+}
+
+/// Body of `POST /cities`, coordinates are looked up when missing.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NewCity {
+    pub name: String,
+    pub lat: Option<f64>,
+    pub long: Option<f64>,
+    #[serde(default)]
+    pub coastal: bool,
+}
+
+/// Body of `PATCH /cities/{id}`, missing fields are kept.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CityPatch {
+    pub name: Option<String>,
+    pub lat: Option<f64>,
+    pub long: Option<f64>,
+    pub coastal: Option<bool>,
+}
+
+fn check_days(days: Option<u8>) -> Result<u8, ApiError> {
+    match days.unwrap_or(DEFAULT_FORECAST_DAYS) {
+        d @ 1..=MAX_FORECAST_DAYS => Ok(d),
+        d => Err(ApiError::BadRequest(format!(
+            "days must be between 1 and {MAX_FORECAST_DAYS}, got {d}"
+        ))),
+    }
+}
+
+fn parse_id(id: &str) -> Result<i64, ApiError> {
+    id.parse()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid city id '{id}'")))
+}
+
+fn not_found(id: i64) -> ApiError {
+    ApiError::NotFound(format!("No city with id {id}"))
+}
+
+#[utoipa::path(get, path = "/cities", tag = "cities",
+    responses((status = 200, description = "Stored cities", body = Vec<StoredCity>)))]
+async fn list_cities(State(state): State<AppState>) -> ApiResult<Vec<StoredCity>> {
+    Ok(Json(state.store()?.cities()?))
+}
+
+#[utoipa::path(post, path = "/cities", tag = "cities", request_body = NewCity,
+    responses(
+        (status = 201, description = "City added", body = StoredCity),
+        (status = 400, description = "Invalid city", body = ErrorBody),
+        (status = 404, description = "Name not found by geocoding", body = ErrorBody),
+        (status = 409, description = "Name already taken", body = ErrorBody),
+        (status = 502, description = "Geocoding failed", body = ErrorBody)))]
+async fn create_city(
+    State(state): State<AppState>,
+    body: Result<Json<NewCity>, JsonRejection>,
+) -> Result<(StatusCode, Json<StoredCity>), ApiError> {
+    let Json(new) = body?;
+    let city = match (new.lat, new.long) {
+        (Some(lat), Some(long)) => City {
+            name: new.name,
+            lat,
+            long,
+            coastal: new.coastal,
+        },
+        (None, None) => {
+            if new.name.trim().is_empty() {
+                return Err(CityError::EmptyName.into());
+            }
+            let response = get_geocode(&new.name).await.map_err(ApiError::Upstream)?;
+            City {
+                coastal: new.coastal,
+                ..City::try_from(response)
+                    .map_err(|_| ApiError::NotFound(format!("No city found for '{}'", new.name)))?
+            }
+        }
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Give both lat and long, or none of them".to_string(),
+            ));
+        }
+    };
+
+    let stored = state.store()?.add_city(&city)?;
+    Ok((StatusCode::CREATED, Json(stored)))
+}
+
+#[utoipa::path(get, path = "/cities/{city}", tag = "cities", params(("city" = i64, Path, description = "City id")),
+    responses(
+        (status = 200, description = "The city", body = StoredCity),
+        (status = 404, description = "Unknown id", body = ErrorBody)))]
+async fn get_city(State(state): State<AppState>, Path(id): Path<String>) -> ApiResult<StoredCity> {
+    let id = parse_id(&id)?;
+    let city = state.store()?.city(id)?.ok_or_else(|| not_found(id))?;
+    Ok(Json(city))
+}
+
+#[utoipa::path(patch, path = "/cities/{city}", tag = "cities", params(("city" = i64, Path, description = "City id")),
+    request_body = CityPatch,
+    responses(
+        (status = 200, description = "Updated city", body = StoredCity),
+        (status = 400, description = "Invalid city", body = ErrorBody),
+        (status = 404, description = "Unknown id", body = ErrorBody),
+        (status = 409, description = "Name already taken", body = ErrorBody)))]
+async fn update_city(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    body: Result<Json<CityPatch>, JsonRejection>,
+) -> ApiResult<StoredCity> {
+    let id = parse_id(&id)?;
+    let Json(changes) = body?;
+    let store = state.store()?;
+    let current = store.city(id)?.ok_or_else(|| not_found(id))?.city;
+
+    let city = City {
+        name: changes.name.unwrap_or(current.name),
+        lat: changes.lat.unwrap_or(current.lat),
+        long: changes.long.unwrap_or(current.long),
+        coastal: changes.coastal.unwrap_or(current.coastal),
+    };
+    let updated = store.update_city(id, &city)?.ok_or_else(|| not_found(id))?;
+    Ok(Json(updated))
+}
+
+#[utoipa::path(delete, path = "/cities/{city}", tag = "cities", params(("city" = i64, Path, description = "City id")),
+    responses(
+        (status = 204, description = "City removed"),
+        (status = 404, description = "Unknown id", body = ErrorBody)))]
+async fn delete_city(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let id = parse_id(&id)?;
+    if !state.store()?.remove_city(id)? {
+        return Err(not_found(id));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Forecast from the cache, or from open-meteo (one upstream call for concurrent requests).
+async fn cached_forecast(
+    state: &AppState,
+    lat: f64,
+    long: f64,
+    days: u8,
+    with_ensemble: bool,
+) -> Result<Arc<Cached<ForecastResponse>>, ApiError> {
+    let variables = format!("daily={DAILY_VARIABLES}&days={days}&ensemble={with_ensemble}");
+    let key = CacheKey::new(lat, long, variables);
+    let (lat, long) = key.coordinates();
+    state
+        .forecasts
+        .get_or_fetch(&key, || async move {
+            let mut forecast = get_forecast(lat, long, days).await?;
+            wmo::annotate(&mut forecast);
+            // the spread is an extra: without it the forecast is still worth serving
+            if with_ensemble {
+                match get_ensemble(lat, long, days.min(MAX_ENSEMBLE_DAYS)).await {
+                    Ok(members) => ensemble::annotate(&mut forecast, &members),
+                    Err(e) => eprintln!("No ensemble for {lat}, {long}: {e:#}"),
+                }
+            }
+            Ok(forecast)
+        })
+        .await
+        .map_err(ApiError::Upstream)
+}
+
+/// 64-bit FNV-1a: a fixed algorithm, so ETags stay the same across builds and restarts.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// JSON response with Cache-Control & ETag, or an empty 304 if the client already has it.
+fn revalidated_json<T: Serialize>(
+    value: &T,
+    max_age: Duration,
+    headers: &HeaderMap,
+) -> Result<Response, ApiError> {
+    let body = serde_json::to_vec(value).map_err(|e| ApiError::Internal(e.into()))?;
+    let etag = format!("\"{:016x}\"", fnv1a(&body));
+
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", max_age.as_secs()),
+        ),
+    ];
+    let matches = headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| {
+            let tag = tag.trim();
+            tag == "*" || tag.trim_start_matches("W/") == etag
+        });
+    if matches {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let json = HeaderValue::from_static("application/json");
+    Ok((cache_headers, [(header::CONTENT_TYPE, json)], body).into_response())
+}
+
+#[utoipa::path(get, path = "/cities/{city}/forecast", tag = "forecast",
+    params(("city" = String, Path, description = "Name of a stored city"), DaysQuery),
+    responses(
+        (status = 200, description = "Daily forecast", body = ForecastResponse, headers(
+            ("ETag" = String, description = "Revalidate with If-None-Match"),
+            ("Cache-Control" = String, description = "Seconds left before the cached forecast expires"))),
+        (status = 304, description = "Not modified since the given ETag"),
+        (status = 400, description = "Invalid days", body = ErrorBody),
+        (status = 404, description = "City not in the list", body = ErrorBody),
+        (status = 502, description = "open-meteo failed", body = ErrorBody)))]
+async fn city_forecast(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    query: Result<Query<DaysQuery>, QueryRejection>,
+) -> Result<Response, ApiError> {
+    let Query(query) = query?;
+    let days = check_days(query.days)?;
+    let city = state
+        .store()?
+        .find_city(&name)?
+        .ok_or_else(|| ApiError::NotFound(format!("'{name}' is not in the city list")))?
+        .city;
+
+    let cached = cached_forecast(&state, city.lat, city.long, days, query.ensemble).await?;
+    let max_age = cached.remaining(state.forecasts.ttl());
+    revalidated_json(&cached.value, max_age, &headers)
+}
+
+#[utoipa::path(get, path = "/forecast", tag = "forecast", params(ForecastQuery),
+    responses(
+        (status = 200, description = "Daily forecast", body = ForecastResponse, headers(
+            ("ETag" = String, description = "Revalidate with If-None-Match"),
+            ("Cache-Control" = String, description = "Seconds left before the cached forecast expires"))),
+        (status = 304, description = "Not modified since the given ETag"),
+        (status = 400, description = "Invalid coordinates or days", body = ErrorBody),
+        (status = 502, description = "open-meteo failed", body = ErrorBody)))]
+async fn forecast(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    query: Result<Query<ForecastQuery>, QueryRejection>,
+) -> Result<Response, ApiError> {
+    let Query(query) = query?;
+    validate_coordinates(query.lat, query.long)?;
+    let days = check_days(query.days)?;
+
+    let cached = cached_forecast(&state, query.lat, query.long, days, query.ensemble).await?;
+    let max_age = cached.remaining(state.forecasts.ttl());
+    revalidated_json(&cached.value, max_age, &headers)
+}
+
+#[utoipa::path(get, path = "/geocode", tag = "forecast", params(GeocodeQuery),
+    responses(
+        (status = 200, description = "Best match", body = City),
+        (status = 400, description = "Empty name", body = ErrorBody),
+        (status = 404, description = "No match", body = ErrorBody),
+        (status = 502, description = "Geocoding failed", body = ErrorBody)))]
+async fn geocode(query: Result<Query<GeocodeQuery>, QueryRejection>) -> ApiResult<City> {
+    let Query(query) = query?;
+    if query.name.trim().is_empty() {
+        return Err(ApiError::BadRequest("name can't be empty".to_string()));
+    }
+
+    let response = get_geocode(&query.name).await.map_err(ApiError::Upstream)?;
+    // no results is the only way the conversion fails
+    let city = City::try_from(response)
+        .map_err(|_| ApiError::NotFound(format!("No city found for '{}'", query.name)))?;
+    Ok(Json(city))
+}
@@ -0,0 +1,189 @@
+use crate::display::{RenderOptions, label_for};
+use crate::models::ForecastResponse;
+use crate::table::print_rows;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, TimeDelta, TimeZone};
+use chrono_tz::Tz;
+
+// Sunrise, sunset and hours of light. Open-meteo gives the times as local wall clock
+// strings: they are read in the timezone of the response, so that days after a daylight
+// saving change get the right offset.
+
+/// Sun of one day.
+#[derive(Debug)]
+pub struct SunDay {
+    pub date: String,
+    pub sunrise: DateTime<Tz>,
+    pub sunset: DateTime<Tz>,
+    pub daylight: TimeDelta,
+    /// Direct sunshine, `None` when the model doesn't give it
+    pub sunshine: Option<TimeDelta>,
+}
+
+/// Timezone of the forecast
+pub fn timezone(forecast: &ForecastResponse) -> Result<Tz> {
+    forecast
+        .timezone
+        .parse()
+        .with_context(|| format!("Unknown timezone '{}'", forecast.timezone))
+}
+
+/// "2026-10-18T08:12" as a time of `tz`
+fn parse_local(time: &str, tz: Tz) -> Result<DateTime<Tz>> {
+    let local = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M")
+        .with_context(|| format!("Invalid time '{time}'"))?;
+    // an ambiguous time (the hour repeated in autumn) is taken the first time round
+    tz.from_local_datetime(&local)
+        .earliest()
+        .with_context(|| format!("{time} doesn't exist in {tz}"))
+}
+
+fn seconds(s: f64) -> TimeDelta {
+    TimeDelta::seconds(s.round() as i64)
+}
+
+/// Sun of day `id`, `None` if the forecast came without it.
+pub fn day(forecast: &ForecastResponse, tz: Tz, id: usize) -> Result<Option<SunDay>> {
+    let d = &forecast.daily;
+    let (Some(sunrise), Some(sunset), Some(&daylight)) = (
+        d.sunrise.get(id),
+        d.sunset.get(id),
+        d.daylight_duration.get(id),
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some(SunDay {
+        date: d.time[id].clone(),
+        sunrise: parse_local(sunrise, tz)?,
+        sunset: parse_local(sunset, tz)?,
+        daylight: seconds(daylight),
+        sunshine: d.sunshine_duration.get(id).copied().flatten().map(seconds),
+    }))
+}
+
+/// Each day of the forecast which has the sun.
+pub fn daily(forecast: &ForecastResponse) -> Result<Vec<SunDay>> {
+    let tz = timezone(forecast)?;
+    let mut days = vec![];
+    for id in 0..forecast.len() {
+        days.extend(day(forecast, tz, id)?);
+    }
+    Ok(days)
+}
+
+/// "10 h 29 min"
+pub fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes();
+    format!("{} h {:02} min", minutes / 60, minutes % 60)
+}
+
+/// "+3 min": how much longer the day is than the one before, "-" for the first one.
+fn daylight_change(previous: Option<&SunDay>, day: &SunDay) -> String {
+    previous.map_or("-".to_string(), |previous| {
+        let minutes = (day.daylight - previous.daylight).num_minutes();
+        format!("{minutes:+} min")
+    })
+}
+
+/// One row per day: sunrise, sunset, daylight (and how it changes) and sunshine.
+pub fn print_sun(forecast: &ForecastResponse, opts: &RenderOptions) -> Result<()> {
+    let days = daily(forecast)?;
+    if days.is_empty() {
+        println!("No sun data available");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = days
+        .iter()
+        .enumerate()
+        .map(|(id, day)| {
+            let previous = id.checked_sub(1).map(|previous| &days[previous]);
+            vec![
+                format!("{} {}", label_for(id, &day.date, opts), day.date),
+                day.sunrise.format("%H:%M %Z").to_string(),
+                day.sunset.format("%H:%M %Z").to_string(),
+                format_duration(day.daylight),
+                daylight_change(previous, day),
+                day.sunshine.map_or("-".to_string(), format_duration),
+            ]
+        })
+        .collect();
+
+    let headers = ["Day", "Sunrise", "Sunset", "Daylight", "Change", "Sunshine"];
+    print_rows(&headers, &rows, &[0, 1, 2], opts);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ForecastBuilder;
+
+    const BRUSSELS: Tz = chrono_tz::Europe::Brussels;
+
+    /// Two days around the start (29 March 2026) and the end (25 October 2026) of summer time.
+    fn forecast() -> ForecastResponse {
+        ForecastBuilder::default()
+            .timezone("Europe/Brussels")
+            .day("2026-03-28", 3, 2.0, 11.0)
+            .sun("2026-03-28T06:58", "2026-03-28T19:23", 44_700.0)
+            .day("2026-03-29", 3, 3.0, 12.0)
+            .sun("2026-03-29T07:56", "2026-03-29T20:25", 44_940.0)
+            .day("2026-10-24", 3, 6.0, 14.0)
+            .sun("2026-10-24T08:23", "2026-10-24T18:24", 36_060.0)
+            .day("2026-10-25", 3, 6.0, 14.0)
+            .sun("2026-10-25T07:25", "2026-10-25T17:22", 35_820.0)
+            .build()
+    }
+
+    #[test]
+    fn times_get_the_offset_of_their_day() {
+        let days = daily(&forecast()).unwrap();
+        let offsets: Vec<String> = days
+            .iter()
+            .map(|day| day.sunrise.format("%H:%M %Z").to_string())
+            .collect();
+        assert_eq!(
+            offsets,
+            ["06:58 CET", "07:56 CEST", "08:23 CEST", "07:25 CET"]
+        );
+        // the clock moved an hour, the sunrise only two minutes (in UTC)
+        let utc = |day: &SunDay| day.sunrise.naive_utc().time();
+        assert_eq!((utc(&days[1]) - utc(&days[0])).num_minutes(), -2);
+        assert_eq!(days[1].daylight, TimeDelta::minutes(749));
+    }
+
+    #[test]
+    fn repeated_autumn_hour_is_taken_the_first_time() {
+        let time = parse_local("2026-10-25T02:30", BRUSSELS).unwrap();
+        assert_eq!(time.format("%H:%M %z").to_string(), "02:30 +0200");
+        assert_eq!(time.naive_utc().to_string(), "2026-10-25 00:30:00");
+    }
+
+    #[test]
+    fn skipped_spring_hour_is_an_error() {
+        let error = parse_local("2026-03-29T02:30", BRUSSELS).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "2026-03-29T02:30 doesn't exist in Europe/Brussels"
+        );
+        assert!(parse_local("2026-03-29 02:30", BRUSSELS).is_err());
+    }
+
+    #[test]
+    fn daylight_change_from_the_day_before() {
+        let days = daily(&forecast()).unwrap();
+        assert_eq!(daylight_change(None, &days[0]), "-");
+        assert_eq!(daylight_change(Some(&days[0]), &days[1]), "+4 min");
+        assert_eq!(daylight_change(Some(&days[2]), &days[3]), "-4 min");
+    }
+
+    #[test]
+    fn forecasts_without_the_sun_have_no_days() {
+        let forecast = ForecastBuilder::default()
+            .day("2026-10-18", 3, 6.0, 14.0)
+            .build();
+        assert!(daily(&forecast).unwrap().is_empty());
+        assert_eq!(format_duration(TimeDelta::seconds(37_740)), "10 h 29 min");
+    }
+}
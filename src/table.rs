@@ -0,0 +1,184 @@
+use crate::args::SortBy;
+use crate::color;
+use crate::display::{RenderOptions, label_for, pad_left, pad_right, width};
+use crate::models::{City, ForecastResponse};
+use crate::wmo::{icon, is_severe};
+
+/// Sort the rows in place, using the first selected day as the reference.
+pub fn sort_rows(rows: &mut [(City, ForecastResponse)], sort: SortBy, day: usize) {
+    // rank: the higher the value, the higher in the table
+    let rank = |forecast: &ForecastResponse| -> Option<f64> {
+        let daily = &forecast.daily;
+        match sort {
+            SortBy::Max => daily.temperature_2m_max.get(day).copied(),
+            SortBy::Min => daily.temperature_2m_min.get(day).map(|t| -t),
+            SortBy::Rain => daily.precipitation_sum.get(day).copied(),
+            SortBy::Name => None,
+        }
+    };
+
+    match sort {
+        SortBy::Name => rows.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name)),
+        _ => rows.sort_by(|(_, a), (_, b)| match (rank(a), rank(b)) {
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            // cities without data for that day go last
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        }),
+    }
+}
+
+/// One cell of the table: icon, min/max and precipitation for a day.
+fn cell(forecast: &ForecastResponse, id: usize, opts: &RenderOptions) -> String {
+    if id >= forecast.len() {
+        return "no data".to_string();
+    }
+    let daily = &forecast.daily;
+    let u = &forecast.daily_units.temperature_2m_min;
+    let code = daily.weather_code[id];
+    let icon = icon(code, opts.unicode);
+    let icon = if is_severe(code) {
+        color::severe(icon, opts.color)
+    } else {
+        icon.to_string()
+    };
+    let temp = |t: f64| color::temperature(&format!("{t:>5.1}"), t, opts.color);
+    format!(
+        "{icon} {}/{} {u} {:>4.1} mm",
+        temp(daily.temperature_2m_min[id]),
+        temp(daily.temperature_2m_max[id]),
+        daily.precipitation_sum[id],
+    )
+}
+
+/// Print a compact comparison table: one row per city, one column per selected day.
+pub fn print_comparison_table(
+    rows: &[(City, ForecastResponse)],
+    days: &[usize],
+    opts: &RenderOptions,
+) {
+    let rows: Vec<(&str, &ForecastResponse)> = rows
+        .iter()
+        .map(|(city, forecast)| (city.name.as_str(), forecast))
+        .collect();
+    print_forecast_table("City", &rows, days, opts);
+}
+
+/// Same table with any label in the first column (a city, a weather model...).
+pub fn print_forecast_table(
+    title: &str,
+    rows: &[(&str, &ForecastResponse)],
+    days: &[usize],
+    opts: &RenderOptions,
+) {
+    if rows.is_empty() || days.is_empty() {
+        println!("No forecast data available");
+        return;
+    }
+
+    // Header texts, using the date of the first city which has it
+    let headers: Vec<String> = days
+        .iter()
+        .map(|&id| {
+            let date = rows
+                .iter()
+                .find_map(|(_, f)| f.daily.time.get(id))
+                .map(String::as_str)
+                .unwrap_or("");
+            format!("{} {date}", label_for(id, date, opts))
+        })
+        .collect();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|(_, forecast)| days.iter().map(|&id| cell(forecast, id, opts)).collect())
+        .collect();
+
+    // Column widths (display width, not bytes)
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| width(name))
+        .chain([width(title)])
+        .max()
+        .unwrap_or(0);
+    let col_widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(col, header)| {
+            cells
+                .iter()
+                .map(|row| width(&row[col]))
+                .chain([width(header)])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let b = opts.box_chars();
+    let mut header_line = format!(" {} ", pad_right(title, name_width));
+    let mut rule = b.line.repeat(name_width + 2);
+    for (header, &w) in headers.iter().zip(&col_widths) {
+        header_line.push_str(&format!("{} {} ", b.column, pad_right(header, w)));
+        rule.push_str(&format!("{}{}", b.cross, b.line.repeat(w + 2)));
+    }
+    println!("{header_line}");
+    println!("{rule}");
+
+    for ((name, _), row) in rows.iter().zip(&cells) {
+        let mut line = format!(" {} ", pad_right(name, name_width));
+        for (cell, &w) in row.iter().zip(&col_widths) {
+            line.push_str(&format!("{} {} ", b.column, pad_left(cell, w)));
+        }
+        println!("{line}");
+    }
+}
+
+/// Print indented rows under a header line, each column as wide as its content.
+/// Columns listed in `text_columns` are aligned on the left, the others (numbers) on the right.
+pub fn print_rows(
+    headers: &[&str],
+    rows: &[Vec<String>],
+    text_columns: &[usize],
+    opts: &RenderOptions,
+) {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(col, header)| {
+            rows.iter()
+                .filter_map(|row| row.get(col))
+                .map(|cell| width(cell))
+                .chain([width(header)])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let b = opts.box_chars();
+    let separator = format!(" {} ", b.column);
+    let header: Vec<String> = headers
+        .iter()
+        .zip(&widths)
+        .map(|(header, &w)| pad_right(header, w))
+        .collect();
+    let rule: Vec<String> = widths.iter().map(|&w| b.line.repeat(w)).collect();
+    println!("   {}", header.join(&separator).trim_end());
+    println!(
+        "   {}",
+        rule.join(&format!("{}{}{}", b.line, b.cross, b.line))
+    );
+
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(col, (cell, &w))| {
+                if text_columns.contains(&col) {
+                    pad_right(cell, w)
+                } else {
+                    pad_left(cell, w)
+                }
+            })
+            .collect();
+        println!("   {}", cells.join(&separator).trim_end());
+    }
+}
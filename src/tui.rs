@@ -0,0 +1,593 @@
+use crate::db::{CityError, Store, StoredCity};
+use crate::display::day_label;
+use crate::models::{City, ForecastResponse, HourlyResponse};
+use crate::open_meteo::{get_city_forecast, get_geocode, get_hourly_forecast};
+use crate::wind;
+use crate::wmo::{describe, emoji, is_severe};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Axis, Block, Chart, Dataset, GraphType, List, ListItem, ListState, Paragraph, Sparkline, Wrap,
+};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+// Full-screen dashboard: city list on the left, daily cards and the next hours on the right.
+// Fetching happens in tokio tasks which report back through a channel, so the UI never blocks.
+
+/// Hours shown in the hourly chart.
+const HOURS: u16 = 24;
+/// Hours between two values of the wind line.
+const WIND_STEP_HOURS: usize = 3;
+/// Width of one daily card.
+const CARD_WIDTH: u16 = 18;
+const CITY_LIST_WIDTH: u16 = 26;
+/// Status while forecasts are fetched, cleared once they are all in.
+const REFRESHING: &str = "Refreshing...";
+
+/// Everything the event loop reacts to.
+enum Message {
+    Key(KeyEvent),
+    Resize,
+    Weather {
+        name: String,
+        result: Result<Box<CityWeather>, String>,
+    },
+    Found(Result<City, String>),
+}
+
+struct CityWeather {
+    daily: ForecastResponse,
+    hourly: HourlyResponse,
+}
+
+enum Mode {
+    Browse,
+    /// Typing the name of a city to add.
+    Search(String),
+}
+
+struct App {
+    store: Store,
+    cities: Vec<StoredCity>,
+    list: ListState,
+    weather: HashMap<String, Box<CityWeather>>,
+    errors: HashMap<String, String>,
+    mode: Mode,
+    status: String,
+    /// Forecasts asked and not answered yet
+    pending: usize,
+    last_refresh: Instant,
+    tx: mpsc::UnboundedSender<Message>,
+    quit: bool,
+}
+
+/// Start the dashboard for the stored cities, refreshing all of them every `refresh`.
+/// Cities added or removed in the dashboard are saved in the database.
+pub async fn run(store: Store, refresh: Duration) -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // crossterm's reads are blocking, so they get their own thread
+    let events = tx.clone();
+    std::thread::spawn(move || {
+        while let Ok(ev) = event::read() {
+            let msg = match ev {
+                Event::Key(key) if key.kind == KeyEventKind::Press => Message::Key(key),
+                Event::Resize(..) => Message::Resize,
+                _ => continue,
+            };
+            if events.send(msg).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut app = App::new(store, tx)?;
+    app.refresh_all();
+    let mut ticker = tokio::time::interval(refresh);
+    ticker.tick().await; // the first tick is immediate, we just refreshed
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal, &mut rx, &mut ticker).await;
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn new(store: Store, tx: mpsc::UnboundedSender<Message>) -> Result<Self> {
+        let cities = store.cities()?;
+        let mut list = ListState::default();
+        if !cities.is_empty() {
+            list.select(Some(0));
+        }
+        Ok(App {
+            store,
+            cities,
+            list,
+            weather: HashMap::new(),
+            errors: HashMap::new(),
+            mode: Mode::Browse,
+            status: String::new(),
+            pending: 0,
+            last_refresh: Instant::now(),
+            tx,
+            quit: false,
+        })
+    }
+
+    async fn event_loop(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        rx: &mut mpsc::UnboundedReceiver<Message>,
+        ticker: &mut tokio::time::Interval,
+    ) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            tokio::select! {
+                Some(msg) = rx.recv() => self.handle(msg),
+                _ = ticker.tick() => self.refresh_all(),
+            }
+        }
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<&City> {
+        self.list
+            .selected()
+            .and_then(|i| self.cities.get(i))
+            .map(|stored| &stored.city)
+    }
+
+    fn refresh_all(&mut self) {
+        let cities: Vec<City> = self.cities.iter().map(|s| s.city.clone()).collect();
+        for city in &cities {
+            self.refresh(city);
+        }
+        self.last_refresh = Instant::now();
+    }
+
+    /// Fetch daily & hourly forecasts in the background.
+    fn refresh(&mut self, city: &City) {
+        self.pending += 1;
+        let tx = self.tx.clone();
+        let city = city.clone();
+        tokio::spawn(async move {
+            let (daily, hourly) = tokio::join!(
+                get_city_forecast(&city),
+                get_hourly_forecast(city.lat, city.long, HOURS)
+            );
+            let result = match (daily, hourly) {
+                (Ok(daily), Ok(hourly)) => Ok(Box::new(CityWeather { daily, hourly })),
+                (Err(e), _) | (_, Err(e)) => Err(format!("{e:#}")),
+            };
+            let _ = tx.send(Message::Weather {
+                name: city.name,
+                result,
+            });
+        });
+    }
+
+    /// Geocode a city name in the background.
+    fn search(&self, name: String) {
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let result = match get_geocode(&name).await {
+                Ok(response) => City::try_from(response).map_err(|e| format!("{name}: {e}")),
+                Err(e) => Err(format!("{e:#}")),
+            };
+            let _ = tx.send(Message::Found(result));
+        });
+    }
+
+    fn handle(&mut self, msg: Message) {
+        match msg {
+            Message::Key(key) => self.handle_key(key),
+            Message::Resize => {}
+            Message::Weather { name, result } => {
+                self.pending = self.pending.saturating_sub(1);
+                if self.pending == 0 && self.status == REFRESHING {
+                    self.status.clear();
+                }
+                // the city may have been removed while it was fetched
+                if !self.cities.iter().any(|stored| stored.city.name == name) {
+                    return;
+                }
+                match result {
+                    Ok(weather) => {
+                        self.errors.remove(&name);
+                        self.weather.insert(name, weather);
+                    }
+                    Err(e) => {
+                        self.errors.insert(name, e);
+                    }
+                }
+            }
+            Message::Found(Ok(city)) => match self.store.add_city(&city) {
+                Ok(stored) => {
+                    self.status = format!("Added {}", stored.city);
+                    self.refresh(&stored.city);
+                    self.cities.push(stored);
+                    self.list.select(Some(self.cities.len() - 1));
+                }
+                Err(e) => {
+                    if let Some(CityError::Duplicate(name)) = e.downcast_ref::<CityError>() {
+                        let existing = self.cities.iter().position(|c| &c.city.name == name);
+                        self.list.select(existing);
+                    }
+                    self.status = format!("{e:#}");
+                }
+            },
+            Message::Found(Err(e)) => self.status = e,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        if let Mode::Search(query) = &mut self.mode {
+            match key.code {
+                KeyCode::Char(c) => query.push(c),
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Enter => {
+                    let query = query.trim().to_string();
+                    self.mode = Mode::Browse;
+                    if !query.is_empty() {
+                        self.status = format!("Searching '{query}'...");
+                        self.search(query);
+                    }
+                }
+                KeyCode::Esc => self.mode = Mode::Browse,
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+            KeyCode::Home => self.list.select_first(),
+            KeyCode::End => self.list.select_last(),
+            KeyCode::Char('/') | KeyCode::Char('a') => self.mode = Mode::Search(String::new()),
+            KeyCode::Char('r') => {
+                self.refresh_all();
+                if self.pending > 0 {
+                    self.status = REFRESHING.to_string();
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(i) = self.list.selected().filter(|&i| i < self.cities.len()) {
+                    if let Err(e) = self.store.remove_city(self.cities[i].id) {
+                        self.status = format!("{e:#}");
+                        return;
+                    }
+                    let StoredCity { city, .. } = self.cities.remove(i);
+                    self.weather.remove(&city.name);
+                    self.errors.remove(&city.name);
+                    self.status = format!("Removed {}", city.name);
+                    if self.cities.is_empty() {
+                        self.list.select(None);
+                    } else {
+                        self.list.select(Some(i.min(self.cities.len() - 1)));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Length(CITY_LIST_WIDTH), Constraint::Min(0)])
+                .areas(main);
+
+        self.draw_cities(frame, left);
+        self.draw_weather(frame, right);
+        self.draw_footer(frame, footer);
+    }
+
+    fn draw_cities(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .cities
+            .iter()
+            .map(|StoredCity { city, .. }| {
+                let summary = match self.weather.get(&city.name) {
+                    Some(w) if !w.daily.is_empty() => format!(
+                        " {} {:.0}°",
+                        emoji(w.daily.daily.weather_code[0]),
+                        w.daily.daily.temperature_2m_max[0]
+                    ),
+                    _ if self.errors.contains_key(&city.name) => " !".to_string(),
+                    _ => String::new(),
+                };
+                ListItem::new(format!("{}{summary}", city.name))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Cities "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.list);
+    }
+
+    fn draw_weather(&self, frame: &mut Frame, area: Rect) {
+        let Some(city) = self.selected() else {
+            let help = Paragraph::new("No city yet, press / to add one.").block(Block::bordered());
+            frame.render_widget(help, area);
+            return;
+        };
+        let block = Block::bordered().title(format!(" {city} "));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let Some(weather) = self.weather.get(&city.name) else {
+            let text = match self.errors.get(&city.name) {
+                Some(e) => format!("Couldn't load the forecast: {e}"),
+                None => "Loading...".to_string(),
+            };
+            frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), inner);
+            return;
+        };
+
+        let [cards, hourly, rain, wind] = Layout::vertical([
+            Constraint::Length(9),
+            Constraint::Min(6),
+            Constraint::Length(4),
+            Constraint::Length(3),
+        ])
+        .areas(inner);
+        draw_cards(frame, cards, &weather.daily);
+        draw_hourly(frame, hourly, &weather.hourly);
+        draw_rain(frame, rain, &weather.hourly);
+        draw_wind(frame, wind, &weather.hourly);
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let line = match &self.mode {
+            Mode::Search(query) => Line::from(vec![
+                Span::styled(" Add city: ", Style::new().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("{query}_")),
+                Span::styled(
+                    "   (Enter to search, Esc to cancel)",
+                    Style::new().fg(Color::DarkGray),
+                ),
+            ]),
+            Mode::Browse => {
+                let minutes = self.last_refresh.elapsed().as_secs() / 60;
+                Line::from(vec![
+                    Span::styled(
+                        " ↑↓ select · / add · d remove · r refresh · q quit ",
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                    Span::raw(format!(" refreshed {minutes} min ago  {}", self.status)),
+                ])
+            }
+        };
+        frame.render_widget(Paragraph::new(line), area);
+    }
+}
+
+/// One small card per day, as many as fit.
+fn draw_cards(frame: &mut Frame, area: Rect, forecast: &ForecastResponse) {
+    let count = usize::min(forecast.len(), (area.width / CARD_WIDTH).max(1) as usize);
+    let areas = Layout::horizontal(vec![Constraint::Ratio(1, count as u32); count]).split(area);
+    let daily = &forecast.daily;
+    let u = &forecast.daily_units.temperature_2m_min;
+
+    for (id, &card) in areas.iter().enumerate() {
+        let code = daily.weather_code[id];
+        let desc_style = if is_severe(code) {
+            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            Style::new()
+        };
+        let text = vec![
+            Line::from(daily.time[id].clone()),
+            Line::from(format!(
+                "{} {:.1}/{:.1} {u}",
+                emoji(code),
+                daily.temperature_2m_min[id],
+                daily.temperature_2m_max[id]
+            )),
+            Line::from(format!("💧 {:.1} mm", daily.precipitation_sum[id])),
+            Line::from(daily_wind(forecast, id)),
+            Line::styled(describe(code), desc_style),
+        ];
+        let block = Block::bordered().title(format!(" {} ", day_label(id)));
+        frame.render_widget(
+            Paragraph::new(text).block(block).wrap(Wrap { trim: true }),
+            card,
+        );
+    }
+}
+
+/// "↙ 25 km/h (48)": strongest wind of the day and its gusts
+fn daily_wind(forecast: &ForecastResponse, id: usize) -> String {
+    let daily = &forecast.daily;
+    match (
+        daily.wind_speed_10m_max.get(id).copied().flatten(),
+        daily.wind_gusts_10m_max.get(id).copied().flatten(),
+        daily.wind_direction_10m_dominant.get(id).copied().flatten(),
+    ) {
+        (Some(speed), Some(gusts), Some(direction)) => format!(
+            "{} {speed:.0} {} ({gusts:.0})",
+            wind::arrow(direction),
+            forecast.daily_units.wind_speed_10m_max
+        ),
+        _ => String::new(),
+    }
+}
+
+/// Hour part of an open-meteo time ("2025-10-18T14:00" => "14:00").
+fn hour(time: &str) -> &str {
+    time.get(11..16).unwrap_or(time)
+}
+
+/// Temperature line for the next hours.
+fn draw_hourly(frame: &mut Frame, area: Rect, forecast: &HourlyResponse) {
+    let block = Block::bordered().title(format!(" Next {HOURS} hours "));
+    if forecast.is_empty() {
+        frame.render_widget(Paragraph::new("No hourly data").block(block), area);
+        return;
+    }
+    let hourly = &forecast.hourly;
+    let points: Vec<(f64, f64)> = hourly
+        .temperature_2m
+        .iter()
+        .enumerate()
+        .map(|(i, &t)| (i as f64, t))
+        .collect();
+    let lo = hourly
+        .temperature_2m
+        .iter()
+        .copied()
+        .fold(f64::MAX, f64::min)
+        .floor()
+        - 1.0;
+    let hi = hourly
+        .temperature_2m
+        .iter()
+        .copied()
+        .fold(f64::MIN, f64::max)
+        .ceil()
+        + 1.0;
+    let last = forecast.len() - 1;
+    let u = &forecast.hourly_units.temperature_2m;
+
+    let dataset = Dataset::default()
+        .name(format!("temperature ({u})"))
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::new().fg(Color::Yellow))
+        .data(&points);
+    let x_axis = Axis::default().bounds([0.0, last as f64]).labels([
+        hour(&hourly.time[0]),
+        hour(&hourly.time[last / 2]),
+        hour(&hourly.time[last]),
+    ]);
+    let y_axis = Axis::default().bounds([lo, hi]).labels([
+        format!("{lo:.0}"),
+        format!("{:.0}", (lo + hi) / 2.0),
+        format!("{hi:.0}"),
+    ]);
+    let chart = Chart::new(vec![dataset])
+        .block(block)
+        .x_axis(x_axis)
+        .y_axis(y_axis);
+    frame.render_widget(chart, area);
+}
+
+/// Wind of every few hours: arrow, speed and gusts, coloured from a gale on.
+fn draw_wind(frame: &mut Frame, area: Rect, forecast: &HourlyResponse) {
+    let hourly = &forecast.hourly;
+    let block = Block::bordered().title(format!(
+        " Wind ({}, gusts) ",
+        forecast.hourly_units.wind_speed_10m
+    ));
+    let spans: Vec<Span> = (0..forecast.len())
+        .step_by(WIND_STEP_HOURS)
+        .map(|id| {
+            let (speed, gusts) = (hourly.wind_speed_10m[id], hourly.wind_gusts_10m[id]);
+            let text = format!(
+                "{} {} {speed:.0} ({gusts:.0})   ",
+                hour(&hourly.time[id]),
+                wind::arrow(hourly.wind_direction_10m[id])
+            );
+            if wind::beaufort(speed) >= wind::SEVERE_BEAUFORT {
+                Span::styled(text, Style::new().fg(Color::Red))
+            } else {
+                Span::raw(text)
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(Line::from(spans))
+            .block(block)
+            .wrap(Wrap { trim: true }),
+        area,
+    );
+}
+
+/// Precipitation probability bars for the next hours.
+fn draw_rain(frame: &mut Frame, area: Rect, forecast: &HourlyResponse) {
+    let data: Vec<u64> = forecast
+        .hourly
+        .precipitation_probability
+        .iter()
+        .map(|p| p.unwrap_or(0) as u64)
+        .collect();
+    let sparkline = Sparkline::default()
+        .block(Block::bordered().title(" Rain probability (0-100 %) "))
+        .style(Style::new().fg(Color::Blue))
+        .max(100)
+        .data(&data);
+    frame.render_widget(sparkline, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestDir;
+
+    fn app(dir: &TestDir) -> App {
+        let store = Store::open(&dir.join("tui.db")).unwrap();
+        let (tx, _) = mpsc::unbounded_channel();
+        App::new(store, tx).unwrap()
+    }
+
+    fn failed(name: &str) -> Message {
+        Message::Weather {
+            name: name.to_string(),
+            result: Err("unreachable".to_string()),
+        }
+    }
+
+    #[test]
+    fn refreshing_is_cleared_once_every_forecast_is_in() {
+        let dir = TestDir::new("tui-refresh");
+        let mut app = app(&dir);
+        let names: Vec<String> = app.cities[..2]
+            .iter()
+            .map(|s| s.city.name.clone())
+            .collect();
+        app.pending = 2;
+        app.status = REFRESHING.to_string();
+
+        app.handle(failed(&names[0]));
+        assert_eq!(app.status, REFRESHING);
+        app.handle(failed(&names[1]));
+        assert_eq!(app.status, "");
+        assert_eq!(app.errors.len(), 2);
+    }
+
+    #[test]
+    fn removed_cities_forget_their_errors() {
+        let dir = TestDir::new("tui-remove");
+        let mut app = app(&dir);
+        let name = app.cities[0].city.name.clone();
+        app.handle(failed(&name));
+        assert!(app.errors.contains_key(&name));
+
+        app.list.select(Some(0));
+        app.handle_key(KeyEvent::from(KeyCode::Char('d')));
+        assert!(!app.cities.iter().any(|s| s.city.name == name));
+        assert!(app.errors.is_empty());
+
+        // a fetch still running when the city was removed
+        app.handle(failed(&name));
+        assert!(app.errors.is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+use crate::db::Snapshot;
+use crate::display::RenderOptions;
+use crate::models::ForecastResponse;
+use crate::table::print_rows;
+use crate::wmo::category;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+// How good the saved forecasts were: every forecast day of every snapshot is matched with what
+// the archive observed on that date. The lead time is the number of days between the fetch and
+// the forecast day (D+0 is a forecast for the day it was fetched).
+// Only the last snapshot of a day counts, the earlier ones are mostly the same forecast.
+
+/// Errors and hits of the forecasts at one lead time.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Score {
+    pub samples: usize,
+    min_error: f64,
+    max_error: f64,
+    hits: usize,
+}
+
+impl Score {
+    fn add(&mut self, other: Score) {
+        self.samples += other.samples;
+        self.min_error += other.min_error;
+        self.max_error += other.max_error;
+        self.hits += other.hits;
+    }
+
+    /// Mean absolute error of the min temperature (°C)
+    pub fn min_mae(&self) -> f64 {
+        self.min_error / self.samples as f64
+    }
+
+    /// Mean absolute error of the max temperature (°C)
+    pub fn max_mae(&self) -> f64 {
+        self.max_error / self.samples as f64
+    }
+
+    /// Percentage of days with the right weather category (rain, clear...)
+    pub fn hit_rate(&self) -> f64 {
+        self.hits as f64 * 100.0 / self.samples as f64
+    }
+}
+
+/// Date a snapshot was fetched, from its "YYYY-MM-DD HH:MM:SS" timestamp
+fn fetched_on(snapshot: &Snapshot) -> Option<NaiveDate> {
+    snapshot.fetched_at.get(..10)?.parse().ok()
+}
+
+/// Forecast days covered by the snapshots, `None` without any snapshot.
+pub fn forecast_range(snapshots: &[Snapshot]) -> Option<(NaiveDate, NaiveDate)> {
+    let dates = snapshots
+        .iter()
+        .flat_map(|snapshot| &snapshot.forecast.daily.time)
+        .filter_map(|date| date.parse::<NaiveDate>().ok());
+    let first = dates.clone().min()?;
+    Some((first, dates.max()?))
+}
+
+/// Scores per lead time (up to `max_lead` days) of the snapshots (oldest first) against the
+/// observed weather. Days the archive doesn't have are left out.
+pub fn verify(
+    snapshots: &[Snapshot],
+    observed: &ForecastResponse,
+    max_lead: i64,
+) -> BTreeMap<i64, Score> {
+    // (fetch date, forecast date) -> day of the last snapshot of that fetch date
+    let mut forecasts = BTreeMap::new();
+    for snapshot in snapshots {
+        let Some(fetched) = fetched_on(snapshot) else {
+            continue;
+        };
+        for (id, date) in snapshot.forecast.daily.time.iter().enumerate() {
+            forecasts.insert((fetched, date.as_str()), (&snapshot.forecast, id));
+        }
+    }
+
+    let observed_days: BTreeMap<&str, usize> = observed
+        .daily
+        .time
+        .iter()
+        .enumerate()
+        .map(|(id, date)| (date.as_str(), id))
+        .collect();
+
+    let mut scores: BTreeMap<i64, Score> = BTreeMap::new();
+    for ((fetched, date), (forecast, id)) in forecasts {
+        let Ok(day) = date.parse::<NaiveDate>() else {
+            continue;
+        };
+        let lead = (day - fetched).num_days();
+        let Some(&seen) = observed_days.get(date) else {
+            continue;
+        };
+        if !(0..=max_lead).contains(&lead) {
+            continue;
+        }
+
+        let (f, o) = (&forecast.daily, &observed.daily);
+        scores.entry(lead).or_default().add(Score {
+            samples: 1,
+            min_error: (f.temperature_2m_min[id] - o.temperature_2m_min[seen]).abs(),
+            max_error: (f.temperature_2m_max[id] - o.temperature_2m_max[seen]).abs(),
+            hits: usize::from(category(f.weather_code[id]) == category(o.weather_code[seen])),
+        });
+    }
+    scores
+}
+
+/// Sum of the scores of several cities, per lead time.
+pub fn combine<'a>(
+    all: impl IntoIterator<Item = &'a BTreeMap<i64, Score>>,
+) -> BTreeMap<i64, Score> {
+    let mut total: BTreeMap<i64, Score> = BTreeMap::new();
+    for scores in all {
+        for (&lead, &score) in scores {
+            total.entry(lead).or_default().add(score);
+        }
+    }
+    total
+}
+
+/// One row per lead time: number of days, MAE of min and max, weather hit rate.
+pub fn print_scores(scores: &BTreeMap<i64, Score>, opts: &RenderOptions) {
+    let rows: Vec<Vec<String>> = scores
+        .iter()
+        .map(|(lead, score)| {
+            vec![
+                format!("D+{lead}"),
+                score.samples.to_string(),
+                format!("{:.1} °C", score.min_mae()),
+                format!("{:.1} °C", score.max_mae()),
+                format!("{:.0} %", score.hit_rate()),
+            ]
+        })
+        .collect();
+    let headers = ["Lead", "Days", "MAE min", "MAE max", "Weather"];
+    print_rows(&headers, &rows, &[0], opts);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ForecastBuilder;
+
+    /// Snapshot of (date, weather code, min, max) days.
+    fn snapshot(version: i64, fetched_at: &str, days: &[(&str, u32, f64, f64)]) -> Snapshot {
+        let forecast = days.iter().fold(
+            ForecastBuilder::default(),
+            |forecast, &(date, code, min, max)| forecast.day(date, code, min, max),
+        );
+        Snapshot {
+            version,
+            fetched_at: fetched_at.to_string(),
+            forecast: forecast.build(),
+        }
+    }
+
+    fn observed() -> ForecastResponse {
+        ForecastBuilder::default()
+            .day("2026-10-10", 61, 5.0, 12.0)
+            .day("2026-10-11", 3, 6.0, 14.0)
+            .day("2026-10-12", 1, 4.0, 16.0)
+            .build()
+    }
+
+    #[test]
+    fn errors_and_hits_per_lead_time() {
+        let snapshots = [snapshot(
+            1,
+            "2026-10-10 07:00:00",
+            &[
+                ("2026-10-10", 63, 6.0, 11.0),
+                ("2026-10-11", 61, 4.0, 15.0),
+                ("2026-10-12", 2, 4.0, 19.0),
+            ],
+        )];
+        let scores = verify(&snapshots, &observed(), 7);
+        assert_eq!(scores.keys().copied().collect::<Vec<_>>(), [0, 1, 2]);
+
+        let today = scores[&0];
+        assert_eq!(today.samples, 1);
+        assert_eq!((today.min_mae(), today.max_mae()), (1.0, 1.0));
+        assert_eq!(today.hit_rate(), 100.0);
+
+        let tomorrow = scores[&1];
+        assert_eq!((tomorrow.min_mae(), tomorrow.max_mae()), (2.0, 1.0));
+        // rain forecast, cloudy observed
+        assert_eq!(tomorrow.hit_rate(), 0.0);
+
+        // clear (1) and cloudy (2) are different categories
+        assert_eq!(scores[&2].max_mae(), 3.0);
+        assert_eq!(scores[&2].hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn only_the_last_snapshot_of_a_day_counts() {
+        let snapshots = [
+            snapshot(1, "2026-10-10 07:00:00", &[("2026-10-11", 61, 0.0, 20.0)]),
+            snapshot(2, "2026-10-10 19:00:00", &[("2026-10-11", 3, 5.0, 15.0)]),
+            // fetched the next day: another lead time
+            snapshot(3, "2026-10-11 07:00:00", &[("2026-10-11", 3, 6.0, 14.0)]),
+        ];
+        let scores = verify(&snapshots, &observed(), 7);
+
+        let lead_1 = scores[&1];
+        assert_eq!(lead_1.samples, 1);
+        assert_eq!((lead_1.min_mae(), lead_1.max_mae()), (1.0, 1.0));
+        assert_eq!(lead_1.hit_rate(), 100.0);
+
+        let lead_0 = scores[&0];
+        assert_eq!(lead_0.samples, 1);
+        assert_eq!((lead_0.min_mae(), lead_0.max_mae()), (0.0, 0.0));
+    }
+
+    #[test]
+    fn days_without_observation_or_too_far_are_left_out() {
+        let snapshots = [
+            snapshot(
+                1,
+                "2026-10-09 07:00:00",
+                &[
+                    // before the fetch, not a forecast
+                    ("2026-10-08", 3, 6.0, 14.0),
+                    ("2026-10-10", 61, 5.0, 12.0),
+                    ("2026-10-12", 1, 4.0, 16.0),
+                    // not observed yet
+                    ("2026-10-13", 1, 4.0, 16.0),
+                ],
+            ),
+            snapshot(2, "not a date", &[("2026-10-10", 61, 5.0, 12.0)]),
+        ];
+        let scores = verify(&snapshots, &observed(), 2);
+        assert_eq!(scores.keys().copied().collect::<Vec<_>>(), [1]);
+        assert_eq!(scores[&1].samples, 1);
+    }
+
+    #[test]
+    fn scores_of_cities_are_added() {
+        let snapshots = [snapshot(
+            1,
+            "2026-10-10 07:00:00",
+            &[("2026-10-10", 61, 7.0, 12.0), ("2026-10-11", 3, 6.0, 14.0)],
+        )];
+        let one = verify(&snapshots, &observed(), 7);
+        let other = verify(
+            &[snapshot(
+                1,
+                "2026-10-10 07:00:00",
+                &[("2026-10-10", 1, 5.0, 16.0)],
+            )],
+            &observed(),
+            7,
+        );
+        let total = combine([&one, &other]);
+        assert_eq!(total[&0].samples, 2);
+        assert_eq!((total[&0].min_mae(), total[&0].max_mae()), (1.0, 2.0));
+        assert_eq!(total[&0].hit_rate(), 50.0);
+        assert_eq!(total[&1].samples, 1);
+    }
+
+    #[test]
+    fn range_of_the_snapshots() {
+        let snapshots = [
+            snapshot(1, "2026-10-10 07:00:00", &[("2026-10-10", 1, 0.0, 0.0)]),
+            snapshot(2, "2026-10-09 07:00:00", &[("2026-10-09", 1, 0.0, 0.0)]),
+            snapshot(3, "2026-10-10 07:00:00", &[("2026-10-16", 1, 0.0, 0.0)]),
+        ];
+        let date = |d: &str| d.parse::<NaiveDate>().unwrap();
+        assert_eq!(
+            forecast_range(&snapshots),
+            Some((date("2026-10-09"), date("2026-10-16")))
+        );
+        assert_eq!(forecast_range(&[]), None);
+    }
+}
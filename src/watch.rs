@@ -0,0 +1,209 @@
+use crate::alerts::{self, Alert, AlertConfig};
+use crate::color;
+use crate::db::Store;
+use crate::diff::{Thresholds, compare};
+use crate::display::RenderOptions;
+use crate::models::{City, ForecastResponse};
+use crate::notify;
+use crate::table::print_comparison_table;
+use anyhow::Result;
+use chrono::Local;
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+// Long running mode: refresh the stored cities on a schedule, show them again (or log what
+// changed), check the alert rules, until SIGINT/SIGTERM.
+// Every refresh of a stored city is saved as a forecast snapshot, for `diff`.
+
+/// Days shown in the table.
+const WATCH_DAYS: [usize; 3] = [0, 1, 2];
+
+pub struct WatchOptions {
+    pub every: Duration,
+    /// Log changes line by line instead of redrawing the table.
+    pub log: bool,
+    pub alerts: Option<AlertConfig>,
+    /// Send new alerts to the sinks of the rules file.
+    pub notify: bool,
+}
+
+/// Resolves on Ctrl-C (SIGINT) or SIGTERM.
+pub async fn shutdown_signal() -> Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
+
+/// Refresh until stopped, a refresh in progress is dropped on shutdown.
+pub async fn run(store: &Store, options: WatchOptions, opts: &RenderOptions) -> Result<()> {
+    let mut state = WatchState {
+        previous: vec![],
+        seen_alerts: HashSet::new(),
+    };
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut ticks = tokio::time::interval(options.every);
+
+    loop {
+        tokio::select! {
+            result = &mut shutdown => {
+                result?;
+                println!("Stopping watch");
+                return Ok(());
+            }
+            _ = ticks.tick() => {}
+        }
+        tokio::select! {
+            result = &mut shutdown => {
+                result?;
+                println!("Stopping watch (refresh interrupted)");
+                return Ok(());
+            }
+            result = state.refresh(store, &options, opts) => {
+                if let Err(e) = result {
+                    eprintln!("{} Refresh failed: {e:#}", timestamp());
+                }
+            }
+        }
+    }
+}
+
+struct WatchState {
+    previous: Vec<(City, ForecastResponse)>,
+    /// Alerts already logged (rule, city, date), forgotten once the date is past.
+    seen_alerts: HashSet<(String, String, String)>,
+}
+
+impl WatchState {
+    async fn refresh(
+        &mut self,
+        store: &Store,
+        options: &WatchOptions,
+        opts: &RenderOptions,
+    ) -> Result<()> {
+        let rules = options.alerts.as_ref().map_or(&[][..], |c| &c.rules[..]);
+        let alerts::Forecasts {
+            fetched: forecasts,
+            failed,
+        } = alerts::fetch_forecasts(store, rules, true).await?;
+        // the other cities are still refreshed, these are tried again next time
+        for e in &failed {
+            eprintln!("{} {e:#}", timestamp());
+        }
+        for (city, forecast) in &forecasts {
+            if let Some(stored) = store.find_city(&city.name)? {
+                store.save_snapshot(stored.id, forecast)?;
+            }
+        }
+
+        if options.log {
+            self.log_changes(&forecasts, opts);
+        } else {
+            if std::io::stdout().is_terminal() {
+                // clear the screen and go back to the top
+                print!("\x1b[2J\x1b[H");
+            }
+            println!(
+                "Updated at {}, next refresh in {} min (Ctrl-C to stop)\n",
+                Local::now().format("%H:%M"),
+                options.every.as_secs() / 60
+            );
+            print_comparison_table(&forecasts, &WATCH_DAYS, opts);
+        }
+
+        if let Some(config) = &options.alerts {
+            self.forget_past_alerts(&Local::now().date_naive().to_string());
+            let fired = alerts::evaluate(&config.rules, &forecasts);
+            self.print_alerts(&fired, options.log, opts);
+            if options.notify && !fired.is_empty() {
+                // failures are reported, the next refresh retries them
+                if let Err(e) = notify::notify(store, &config.sinks, &fired).await {
+                    eprintln!("{} {e:#}", timestamp());
+                }
+            }
+        }
+
+        // a city that failed keeps its last forecast to compare the next one with
+        let missed: Vec<_> = self
+            .previous
+            .drain(..)
+            .filter(|(city, _)| !forecasts.iter().any(|(c, _)| c.name == city.name))
+            .collect();
+        self.previous = forecasts;
+        self.previous.extend(missed);
+        Ok(())
+    }
+
+    /// Dates are "YYYY-MM-DD" so they sort like the days they are.
+    fn forget_past_alerts(&mut self, today: &str) {
+        self.seen_alerts
+            .retain(|(_, _, date)| date.as_str() >= today);
+    }
+
+    /// The table is redrawn every time so it gets every alert, the log only the new ones.
+    fn print_alerts(&mut self, fired: &[Alert], log: bool, opts: &RenderOptions) {
+        let mark = if opts.unicode { "⚠" } else { "!" };
+        for alert in fired {
+            let key = (alert.rule.clone(), alert.city.clone(), alert.date.clone());
+            if self.seen_alerts.insert(key) || !log {
+                let line = color::severe(&format!("{mark} {alert}"), opts.color);
+                println!("{} {line}", timestamp());
+            }
+        }
+    }
+
+    fn log_changes(&self, forecasts: &[(City, ForecastResponse)], opts: &RenderOptions) {
+        if self.previous.is_empty() {
+            println!("{} Watching {} cities", timestamp(), forecasts.len());
+            return;
+        }
+        for (city, forecast) in forecasts {
+            let Some((_, old)) = self.previous.iter().find(|(c, _)| c.name == city.name) else {
+                println!("{} {}: new city", timestamp(), city.name);
+                continue;
+            };
+            for change in compare(old, forecast, Thresholds::default()) {
+                println!("{} {}: {}", timestamp(), city.name, change.describe(opts));
+            }
+        }
+    }
+}
+
+fn timestamp() -> String {
+    Local::now().format("[%Y-%m-%d %H:%M:%S]").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn past_alerts_are_forgotten() {
+        let key = |date: &str| {
+            (
+                "Frost".to_string(),
+                "Brussels".to_string(),
+                date.to_string(),
+            )
+        };
+        let mut state = WatchState {
+            previous: Vec::new(),
+            seen_alerts: HashSet::from([key("2026-10-17"), key("2026-10-18"), key("2026-10-19")]),
+        };
+        state.forget_past_alerts("2026-10-18");
+        assert_eq!(
+            state.seen_alerts,
+            HashSet::from([key("2026-10-18"), key("2026-10-19")])
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::server::AppState;
+use axum::Router;
+use axum::http::header;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+
+// Browser frontend: a single page built into the binary (no files to deploy next to it),
+// everything it shows comes from the JSON API in the server module.
+// `/docs` is the Swagger UI for `/openapi.json`. Its assets are built in too, copied from the
+// swagger-ui 5.17.14 release (web/swagger-ui, Apache 2.0), so the page doesn't run scripts from
+// a CDN. Update them together with the version here.
+
+const INDEX_HTML: &str = include_str!("web/index.html");
+const APP_JS: &str = include_str!("web/app.js");
+const STYLE_CSS: &str = include_str!("web/style.css");
+const DOCS_HTML: &str = include_str!("web/docs.html");
+const SWAGGER_UI_JS: &str = include_str!("web/swagger-ui/swagger-ui-bundle.js");
+const SWAGGER_UI_CSS: &str = include_str!("web/swagger-ui/swagger-ui.css");
+
+/// Routes for the page and its assets, merged into the API router.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(index))
+        .route("/app.js", get(app_js))
+        .route("/style.css", get(style_css))
+        .route("/docs", get(docs))
+        .route("/docs/swagger-ui-bundle.js", get(swagger_ui_js))
+        .route("/docs/swagger-ui.css", get(swagger_ui_css))
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn app_js() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/javascript; charset=utf-8")],
+        APP_JS,
+    )
+}
+
+async fn style_css() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+        STYLE_CSS,
+    )
+}
+
+async fn docs() -> Html<&'static str> {
+    Html(DOCS_HTML)
+}
+
+async fn swagger_ui_js() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/javascript; charset=utf-8")],
+        SWAGGER_UI_JS,
+    )
+}
+
+async fn swagger_ui_css() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+        SWAGGER_UI_CSS,
+    )
+}
@@ -0,0 +1,311 @@
+// deca-weather frontend, only talks to the JSON API of `deca-weather serve`.
+
+const DAYS = 7;
+
+const state = {
+  cities: [], // [{id, name, lat, long}]
+  forecasts: new Map(), // city name -> forecast response (or an Error)
+  day: 0,
+  selected: null, // {name, forecast}
+  found: null, // geocoding result {name, lat, long}
+};
+
+const $ = (id) => document.getElementById(id);
+
+function dayLabel(index, date) {
+  if (index === 0) return "Today";
+  if (index === 1) return "Tomorrow";
+  return new Date(`${date}T00:00`).toLocaleDateString(undefined, {
+    weekday: "short",
+    day: "numeric",
+  });
+}
+
+function fmt(value, unit) {
+  return value == null ? "–" : `${value.toFixed(1)} ${unit}`;
+}
+
+function setStatus(message, isError = false) {
+  const status = $("status");
+  status.textContent = message;
+  status.classList.toggle("error", isError);
+}
+
+// fetch + JSON, API errors come as {"error": "..."}
+async function api(path, options = {}) {
+  const response = await fetch(path, {
+    headers: { "Content-Type": "application/json" },
+    ...options,
+  });
+  if (response.status === 204) return null;
+  const body = await response.json().catch(() => ({}));
+  if (!response.ok) {
+    throw new Error(body.error || `${response.status} ${response.statusText}`);
+  }
+  return body;
+}
+
+async function loadCities() {
+  setStatus("Loading forecasts…");
+  try {
+    state.cities = await api("/cities");
+  } catch (e) {
+    setStatus(`Couldn't load cities: ${e.message}`, true);
+    return;
+  }
+  render();
+
+  await Promise.all(state.cities.map((city) => loadForecast(city.name)));
+  const failed = [...state.forecasts.values()].filter((f) => f instanceof Error).length;
+  setStatus(failed ? `${failed} forecast(s) couldn't be loaded` : "", failed > 0);
+
+  if (!state.selected && state.cities.length) selectCity(state.cities[0].name);
+}
+
+async function loadForecast(name) {
+  try {
+    const forecast = await api(`/cities/${encodeURIComponent(name)}/forecast?days=${DAYS}`);
+    state.forecasts.set(name, forecast);
+  } catch (e) {
+    state.forecasts.set(name, e);
+  }
+  render();
+}
+
+function selectCity(name) {
+  const forecast = state.forecasts.get(name);
+  if (!forecast || forecast instanceof Error) return;
+  state.selected = { name, forecast };
+  render();
+}
+
+async function removeCity(city) {
+  if (!confirm(`Remove ${city.name} from your cities?`)) return;
+  try {
+    await api(`/cities/${city.id}`, { method: "DELETE" });
+  } catch (e) {
+    setStatus(`Couldn't remove ${city.name}: ${e.message}`, true);
+    return;
+  }
+  state.cities = state.cities.filter((c) => c.id !== city.id);
+  state.forecasts.delete(city.name);
+  if (state.selected?.name === city.name) state.selected = null;
+  render();
+}
+
+async function search(name) {
+  setStatus(`Searching ${name}…`);
+  try {
+    const city = await api(`/geocode?name=${encodeURIComponent(name)}`);
+    const forecast = await api(`/forecast?lat=${city.lat}&long=${city.long}&days=${DAYS}`);
+    state.found = { ...city, forecast };
+    state.selected = { name: city.name, forecast };
+    setStatus("");
+  } catch (e) {
+    state.found = null;
+    setStatus(e.message, true);
+  }
+  render();
+}
+
+async function addFound() {
+  const { name, lat, long } = state.found;
+  try {
+    const stored = await api("/cities", {
+      method: "POST",
+      body: JSON.stringify({ name, lat, long }),
+    });
+    state.cities.push(stored);
+    state.forecasts.set(stored.name, state.found.forecast);
+    state.found = null;
+    setStatus(`${stored.name} added to your cities`);
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+  render();
+}
+
+function render() {
+  renderDays();
+  renderSearchResult();
+  renderCards();
+  renderChart();
+}
+
+function renderDays() {
+  const first =
+    state.selected?.forecast ?? [...state.forecasts.values()].find((f) => !(f instanceof Error));
+  const dates = first ? first.daily.time : [];
+  const nav = $("days");
+  nav.replaceChildren(
+    ...dates.map((date, i) => {
+      const button = document.createElement("button");
+      button.textContent = dayLabel(i, date);
+      button.title = date;
+      button.classList.toggle("active", i === state.day);
+      button.onclick = () => {
+        state.day = i;
+        render();
+      };
+      return button;
+    }),
+  );
+}
+
+function dayBlock(forecast, day) {
+  const d = forecast.daily;
+  const u = forecast.daily_units;
+  // icon & severity come from the API (wmo module), no second table of codes here
+  const weather = forecast.weather[day];
+  const block = document.createElement("div");
+  block.innerHTML = `
+    <div class="icon"></div>
+    <div class="weather"></div>
+    <div class="temps">
+      <span class="min"></span> / <span class="max"></span>
+    </div>
+    <div class="rain"></div>`;
+  block.querySelector(".icon").textContent = weather.icon;
+  block.querySelector(".weather").textContent =
+    weather.category.charAt(0).toUpperCase() + weather.category.slice(1);
+  block.querySelector(".weather").title = weather.description;
+  block.querySelector(".min").textContent = fmt(d.temperature_2m_min[day], u.temperature_2m_min);
+  block.querySelector(".max").textContent = fmt(d.temperature_2m_max[day], u.temperature_2m_max);
+  block.querySelector(".rain").textContent =
+    `Rain ${fmt(d.precipitation_sum[day], u.precipitation_sum)}`;
+  return [block, weather.severe];
+}
+
+function renderSearchResult() {
+  const section = $("search-result");
+  section.hidden = !state.found;
+  if (!state.found) return;
+
+  const { name, lat, long, forecast } = state.found;
+  const title = document.createElement("div");
+  title.innerHTML = "<strong></strong><br><small></small>";
+  title.querySelector("strong").textContent = name;
+  title.querySelector("small").textContent = `${lat.toFixed(2)}, ${long.toFixed(2)}`;
+
+  const [block] = dayBlock(forecast, Math.min(state.day, forecast.daily.time.length - 1));
+  block.className = "card";
+
+  const add = document.createElement("button");
+  add.className = "primary";
+  add.textContent = "Add to my cities";
+  add.disabled = state.cities.some((c) => c.name.toLowerCase() === name.toLowerCase());
+  add.onclick = addFound;
+
+  section.replaceChildren(title, block, add);
+}
+
+function renderCards() {
+  $("cards").replaceChildren(
+    ...state.cities.map((city) => {
+      const card = document.createElement("article");
+      card.className = "card";
+      card.classList.toggle("selected", state.selected?.name === city.name);
+
+      const title = document.createElement("h3");
+      title.textContent = city.name;
+      const remove = document.createElement("button");
+      remove.className = "remove";
+      remove.title = "Remove";
+      remove.textContent = "✕";
+      remove.onclick = (event) => {
+        event.stopPropagation();
+        removeCity(city);
+      };
+      title.append(remove);
+      card.append(title);
+
+      const forecast = state.forecasts.get(city.name);
+      if (!forecast) {
+        card.append("Loading…");
+      } else if (forecast instanceof Error) {
+        card.append(forecast.message);
+      } else if (state.day < forecast.daily.time.length) {
+        const [block, severe] = dayBlock(forecast, state.day);
+        card.classList.toggle("severe", severe);
+        card.append(block);
+      }
+      card.onclick = () => selectCity(city.name);
+      return card;
+    }),
+  );
+}
+
+// SVG line chart of min/mean/max with precipitation bars, the selected day highlighted
+function renderChart() {
+  const panel = $("chart-panel");
+  panel.hidden = !state.selected;
+  if (!state.selected) return;
+
+  const { name, forecast } = state.selected;
+  const d = forecast.daily;
+  const unit = forecast.daily_units.temperature_2m_max;
+  $("chart-title").textContent = `${name}: next ${d.time.length} days`;
+
+  const svg = $("chart");
+  const [width, height] = [640, 260];
+  const pad = { left: 40, right: 10, top: 10, bottom: 30 };
+  const plotW = width - pad.left - pad.right;
+  const plotH = height - pad.top - pad.bottom;
+
+  const series = [
+    ["min", d.temperature_2m_min],
+    ["mean", d.temperature_2m_mean],
+    ["max", d.temperature_2m_max],
+  ];
+  const temps = series.flatMap(([, values]) => values).filter((v) => v != null);
+  let lo = Math.floor(Math.min(...temps) - 1);
+  let hi = Math.ceil(Math.max(...temps) + 1);
+  if (hi - lo < 4) [lo, hi] = [lo - 2, hi + 2];
+  const maxRain = Math.max(1, ...d.precipitation_sum.filter((v) => v != null));
+
+  const n = d.time.length;
+  const step = plotW / n;
+  const x = (i) => pad.left + step * (i + 0.5);
+  const y = (t) => pad.top + plotH * (1 - (t - lo) / (hi - lo));
+
+  const parts = [];
+  // horizontal grid with temperature labels
+  for (let i = 0; i <= 4; i++) {
+    const t = lo + ((hi - lo) * i) / 4;
+    parts.push(
+      `<line class="grid" x1="${pad.left}" x2="${width - pad.right}" y1="${y(t)}" y2="${y(t)}"/>`,
+      `<text x="${pad.left - 6}" y="${y(t) + 4}" text-anchor="end">${t.toFixed(0)}${unit}</text>`,
+    );
+  }
+  parts.push(
+    `<rect class="cursor" x="${pad.left + step * state.day}" y="${pad.top}" width="${step}" height="${plotH}"/>`,
+  );
+  d.precipitation_sum.forEach((rain, i) => {
+    if (!rain) return;
+    const h = (plotH / 3) * (rain / maxRain);
+    parts.push(
+      `<rect class="rain" x="${x(i) - step * 0.3}" y="${pad.top + plotH - h}" width="${step * 0.6}" height="${h}"><title>${rain} mm</title></rect>`,
+    );
+  });
+  for (const [cls, values] of series) {
+    const points = values
+      .map((v, i) => (v == null ? null : `${x(i)},${y(v)}`))
+      .filter(Boolean)
+      .join(" ");
+    parts.push(`<polyline class="line ${cls}" points="${points}"/>`);
+  }
+  d.time.forEach((date, i) => {
+    parts.push(
+      `<text x="${x(i)}" y="${height - 10}" text-anchor="middle">${dayLabel(i, date)}</text>`,
+    );
+  });
+  svg.innerHTML = parts.join("");
+}
+
+$("search").addEventListener("submit", (event) => {
+  event.preventDefault();
+  const name = $("search-name").value.trim();
+  if (name) search(name);
+});
+
+loadCities();
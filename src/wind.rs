@@ -0,0 +1,110 @@
+// Wind: compass points and arrows for the direction, the Beaufort scale for the speed.
+// Speeds are in km/h, the open-meteo default. Directions are where the wind comes from
+// (0 is north, 90 east), the arrows point where it blows to.
+
+/// Upper limits (km/h, excluded) of Beaufort forces 0 to 11, anything above is 12.
+const BEAUFORT_LIMITS: [f64; 12] = [
+    1.0, 6.0, 12.0, 20.0, 29.0, 39.0, 50.0, 62.0, 75.0, 89.0, 103.0, 118.0,
+];
+/// From this force on the wind is shown as a warning (gale).
+pub const SEVERE_BEAUFORT: u8 = 8;
+
+/// 8 points compass, for where the wind or the waves come from
+pub fn compass_point(degrees: f64) -> &'static str {
+    const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    POINTS[compass_index(degrees)]
+}
+
+/// Arrow pointing where the wind blows to: a north wind (from the north) is "↓"
+pub fn arrow(degrees: f64) -> &'static str {
+    const ARROWS: [&str; 8] = ["↓", "↙", "←", "↖", "↑", "↗", "→", "↘"];
+    ARROWS[compass_index(degrees)]
+}
+
+fn compass_index(degrees: f64) -> usize {
+    (degrees.rem_euclid(360.0) / 45.0).round() as usize % 8
+}
+
+/// Beaufort force (0 to 12) of a wind speed in km/h
+pub fn beaufort(kmh: f64) -> u8 {
+    BEAUFORT_LIMITS
+        .iter()
+        .position(|&limit| kmh < limit)
+        .unwrap_or(BEAUFORT_LIMITS.len()) as u8
+}
+
+/// Name of a Beaufort force
+pub fn beaufort_description(force: u8) -> &'static str {
+    match force {
+        0 => "calm",
+        1 => "light air",
+        2 => "light breeze",
+        3 => "gentle breeze",
+        4 => "moderate breeze",
+        5 => "fresh breeze",
+        6 => "strong breeze",
+        7 => "near gale",
+        8 => "gale",
+        9 => "strong gale",
+        10 => "storm",
+        11 => "violent storm",
+        _ => "hurricane force",
+    }
+}
+
+/// "↙ NE 25 km/h", or "NE 25 km/h" without unicode
+pub fn format_wind(speed: f64, direction: f64, unit: &str, unicode: bool) -> String {
+    let point = compass_point(direction);
+    if unicode {
+        format!("{} {point} {speed:.0} {unit}", arrow(direction))
+    } else {
+        format!("{point} {speed:.0} {unit}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions_wrap_around() {
+        assert_eq!(compass_point(0.0), "N");
+        assert_eq!(compass_point(360.0), "N");
+        assert_eq!(compass_point(720.0), "N");
+        assert_eq!(compass_point(-45.0), "NW");
+        assert_eq!(compass_point(-90.0), "W");
+        // halfway between two points goes to the next one
+        assert_eq!(compass_point(22.5), "NE");
+        assert_eq!(compass_point(22.4), "N");
+        assert_eq!(compass_point(337.6), "N");
+        assert_eq!(compass_point(225.0), "SW");
+    }
+
+    #[test]
+    fn arrows_point_where_the_wind_blows() {
+        assert_eq!(arrow(0.0), "↓");
+        assert_eq!(arrow(90.0), "←");
+        assert_eq!(arrow(-45.0), "↘");
+        assert_eq!(arrow(360.0), arrow(0.0));
+    }
+
+    #[test]
+    fn beaufort_limits_belong_to_the_next_force() {
+        assert_eq!(beaufort(0.0), 0);
+        assert_eq!(beaufort(0.99), 0);
+        assert_eq!(beaufort(1.0), 1);
+        assert_eq!(beaufort(61.9), 7);
+        assert_eq!(beaufort(62.0), SEVERE_BEAUFORT);
+        assert_eq!(beaufort(117.9), 11);
+        assert_eq!(beaufort(118.0), 12);
+        assert_eq!(beaufort(250.0), 12);
+        assert_eq!(beaufort_description(12), "hurricane force");
+        assert_eq!(beaufort_description(SEVERE_BEAUFORT), "gale");
+    }
+
+    #[test]
+    fn wind_text() {
+        assert_eq!(format_wind(24.6, 45.0, "km/h", true), "↙ NE 25 km/h");
+        assert_eq!(format_wind(24.6, 45.0, "km/h", false), "NE 25 km/h");
+    }
+}
@@ -1,3 +1,9 @@
+use crate::models::{DayWeather, ForecastResponse};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
 // This table was derived using OpenAI from the National Centers for Environmental Information website:
 // https://www.nodc.noaa.gov/archive/arc0021/0002199/1.1/data/0-data/HTML/WMO-CODE/WMO4677.HTM
 
@@ -158,3 +164,113 @@ pub fn emoji(code: u32) -> &'static str {
         _ => "?",
     }
 }
+
+/// Codes worth highlighting: thunderstorms and freezing drizzle/rain.
+pub fn is_severe(code: u32) -> bool {
+    matches!(code, 17 | 24 | 29 | 56 | 57 | 66 | 67 | 91..=99)
+}
+
+/// Plain ASCII stand-in for `emoji`, used with --no-unicode.
+pub fn ascii(code: u32) -> &'static str {
+    match code {
+        0..=3 => "[cloud]",
+        45 | 48 => "[fog]",
+        51..=55 => "[drizzle]",
+        61..=65 => "[rain]",
+        71..=75 => "[snow]",
+        80..=82 => "[showers]",
+        95..=99 => "[storm]",
+        _ => "[?]",
+    }
+}
+
+/// Weather icon for the code, emoji or ASCII.
+pub fn icon(code: u32, unicode: bool) -> &'static str {
+    if unicode { emoji(code) } else { ascii(code) }
+}
+
+/// Broad kind of weather, to match or compare codes without caring about the details.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Clear,
+    Cloudy,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    Thunderstorm,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 8] = [
+        Category::Clear,
+        Category::Cloudy,
+        Category::Fog,
+        Category::Drizzle,
+        Category::Rain,
+        Category::Snow,
+        Category::Thunderstorm,
+        Category::Other,
+    ];
+}
+
+/// Category of a code as used by open-meteo (the WMO codes it actually returns).
+pub fn category(code: u32) -> Category {
+    match code {
+        0 | 1 => Category::Clear,
+        2 | 3 => Category::Cloudy,
+        45 | 48 => Category::Fog,
+        51..=57 => Category::Drizzle,
+        61..=67 | 80..=82 => Category::Rain,
+        71..=77 | 85 | 86 => Category::Snow,
+        95..=99 => Category::Thunderstorm,
+        _ => Category::Other,
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Category::Clear => "clear",
+            Category::Cloudy => "cloudy",
+            Category::Fog => "fog",
+            Category::Drizzle => "drizzle",
+            Category::Rain => "rain",
+            Category::Snow => "snow",
+            Category::Thunderstorm => "thunderstorm",
+            Category::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Category::ALL
+            .into_iter()
+            .find(|category| category.to_string().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown weather category '{name}'"))
+    }
+}
+
+/// Set the icon, category and severity of each forecast day.
+pub fn annotate(forecast: &mut ForecastResponse) {
+    let weather = forecast
+        .daily
+        .weather_code
+        .iter()
+        .map(|&code| DayWeather {
+            icon: emoji(code).to_string(),
+            category: category(code),
+            description: describe(code).to_string(),
+            severe: is_severe(code),
+        })
+        .collect();
+    forecast.weather = Some(weather);
+}